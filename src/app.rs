@@ -1,6 +1,8 @@
 use gtk::prelude::*;
 use relm4::*;
 
+use std::thread;
+
 use crate::{
     components::*,
     page::Page,
@@ -10,6 +12,9 @@ use crate::{
 
 pub struct AppComponents {
     settings_page: RelmComponent<SettingsPage, App>,
+    library_page: RelmComponent<LibraryPage, App>,
+    loading_page: RelmComponent<LoadingPage, App>,
+    gallery_page: RelmComponent<GalleryPage, App>,
     session_page: RelmComponent<SessionPage, App>,
     complete_page: RelmComponent<CompletePage, App>,
     error_page: RelmComponent<ErrorPage, App>,
@@ -19,6 +24,9 @@ impl Components<App> for AppComponents {
     fn init_components(parent_model: &App, parent_sender: Sender<AppMsg>) -> Self {
         Self {
             settings_page: RelmComponent::new(parent_model, parent_sender.clone()),
+            library_page: RelmComponent::new(parent_model, parent_sender.clone()),
+            loading_page: RelmComponent::new(parent_model, parent_sender.clone()),
+            gallery_page: RelmComponent::new(parent_model, parent_sender.clone()),
             session_page: RelmComponent::new(parent_model, parent_sender.clone()),
             complete_page: RelmComponent::new(parent_model, parent_sender.clone()),
             error_page: RelmComponent::new(parent_model, parent_sender.clone()),
@@ -27,6 +35,9 @@ impl Components<App> for AppComponents {
 
     fn connect_parent(&mut self, parent_widgets: &<App as Model>::Widgets) {
         self.settings_page.connect_parent(parent_widgets);
+        self.library_page.connect_parent(parent_widgets);
+        self.loading_page.connect_parent(parent_widgets);
+        self.gallery_page.connect_parent(parent_widgets);
         self.session_page.connect_parent(parent_widgets);
         self.complete_page.connect_parent(parent_widgets);
         self.error_page.connect_parent(parent_widgets);
@@ -35,21 +46,51 @@ impl Components<App> for AppComponents {
 
 pub enum AppMsg {
     ShowSettings,
-    ShowSessionComplete,
+    ShowLibrary(Settings),
+    ShowGallery,
+    ShowSessionComplete(Session),
     ShowError(String),
     StartNewSession(Settings),
+    StartSelectedSession(Settings, Vec<String>),
+    SessionProgress(u64, usize, usize),
+    SessionLoaded(u64, Result<Session, String>),
+    WindowActiveChanged(bool),
 }
 
 pub struct App {
     pub page: Page,
+    pub settings: Settings,
+    loading: u64,
 }
 
 impl App {
-    pub fn new() -> Self {
+    pub fn new(settings: Settings) -> Self {
         Self {
             page: Page::Settings,
+            settings,
+            loading: 0,
         }
     }
+
+    fn load_session<F>(&mut self, components: &AppComponents, sender: Sender<AppMsg>, create: F)
+    where
+        F: FnOnce(&dyn Fn(usize, usize)) -> Result<Session, String> + Send + 'static,
+    {
+        self.loading += 1;
+        self.page = Page::Loading;
+
+        let loading = self.loading;
+        components.loading_page.send(LoadingPageMsg::Start).unwrap();
+
+        thread::spawn(move || {
+            let progress_sender = sender.clone();
+            let progress = move |done: usize, total: usize| {
+                send!(progress_sender, AppMsg::SessionProgress(loading, done, total));
+            };
+
+            send!(sender, AppMsg::SessionLoaded(loading, create(&progress)));
+        });
+    }
 }
 
 impl Model for App {
@@ -59,19 +100,55 @@ impl Model for App {
 }
 
 impl AppUpdate for App {
-    fn update(&mut self, msg: AppMsg, components: &AppComponents, _sender: Sender<AppMsg>) -> bool {
+    fn update(&mut self, msg: AppMsg, components: &AppComponents, sender: Sender<AppMsg>) -> bool {
         match msg {
             AppMsg::ShowSettings => {
                 self.page = Page::Settings;
             },
+            AppMsg::ShowLibrary(settings) => {
+                components.library_page.send(LibraryPageMsg::Open(settings)).unwrap();
+                self.page = Page::Library;
+            },
+            AppMsg::ShowGallery => {
+                components.gallery_page.send(GalleryPageMsg::Open).unwrap();
+                self.page = Page::Gallery;
+            },
+            AppMsg::StartSelectedSession(settings, images) => {
+                self.load_session(components, sender, move |progress| Session::from_selection(&settings, images, progress));
+            },
             AppMsg::StartNewSession(settings) => {
-                let session = Session::from(&settings);
-                components.session_page.send(SessionPageMsg::NewSession(session)).unwrap();
-                self.page = Page::Session;
+                self.load_session(components, sender, move |progress| Session::from(&settings, progress));
+            },
+            AppMsg::SessionProgress(loading, done, total) => {
+                if loading == self.loading && self.page == Page::Loading {
+                    components.loading_page.send(LoadingPageMsg::Progress(done, total)).unwrap();
+                }
+            },
+            AppMsg::SessionLoaded(loading, result) => {
+                if loading != self.loading || self.page != Page::Loading {
+                    return true;
+                }
+
+                match result {
+                    Ok(session) => {
+                        components.session_page.send(SessionPageMsg::NewSession(session)).unwrap();
+                        self.page = Page::Session;
+                    },
+                    Err(error) => {
+                        components.error_page.send(ErrorPageMsg::ErrorMessage(error)).unwrap();
+                        self.page = Page::Error;
+                    },
+                }
             },
-            AppMsg::ShowSessionComplete => {
+            AppMsg::ShowSessionComplete(session) => {
+                components.complete_page.send(CompletePageMsg::SessionCompleted(session)).unwrap();
                 self.page = Page::Complete;
             },
+            AppMsg::WindowActiveChanged(active) => {
+                if self.page == Page::Session {
+                    components.session_page.send(SessionPageMsg::WindowActiveChanged(active)).unwrap();
+                }
+            },
             AppMsg::ShowError(error) => {
                 components.error_page.send(ErrorPageMsg::ErrorMessage(error)).unwrap();
                 self.page = Page::Error;
@@ -88,6 +165,9 @@ impl Widgets<App, ()> for AppWidgets {
             set_title: Some("Quick pose"),
             set_default_width: 600,
             set_default_height: 800,
+            connect_is_active_notify(sender) => move |window| {
+                send!(sender, AppMsg::WindowActiveChanged(window.is_active()));
+            },
             set_titlebar = Some(&gtk::HeaderBar) {
                 set_show_title_buttons: true,
 
@@ -108,6 +188,9 @@ impl Widgets<App, ()> for AppWidgets {
 
     fn post_init() {
         pages.add_named(components.settings_page.root_widget(), Some("settings"));
+        pages.add_named(components.library_page.root_widget(), Some("library"));
+        pages.add_named(components.loading_page.root_widget(), Some("loading"));
+        pages.add_named(components.gallery_page.root_widget(), Some("gallery"));
         pages.add_named(components.session_page.root_widget(), Some("session"));
         pages.add_named(components.complete_page.root_widget(), Some("complete"));
         pages.add_named(components.error_page.root_widget(), Some("error"));
@@ -118,6 +201,9 @@ impl Widgets<App, ()> for AppWidgets {
     fn pre_view() {
         match model.page {
             Page::Settings => self.pages.set_visible_child_name("settings"),
+            Page::Library => self.pages.set_visible_child_name("library"),
+            Page::Loading => self.pages.set_visible_child_name("loading"),
+            Page::Gallery => self.pages.set_visible_child_name("gallery"),
             Page::Session => self.pages.set_visible_child_name("session"),
             Page::Complete => self.pages.set_visible_child_name("complete"),
             Page::Error => self.pages.set_visible_child_name("error"),
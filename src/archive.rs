@@ -0,0 +1,80 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use zip::ZipArchive;
+
+use crate::tags::is_sidecar;
+
+const ARCHIVE_SEPARATOR: &str = "!/";
+
+pub fn is_archive(path: &str) -> bool {
+    match Path::new(path).extension().and_then(|value| value.to_str()) {
+        Some(extension) => extension.eq_ignore_ascii_case("zip") || extension.eq_ignore_ascii_case("cbz"),
+        None => false,
+    }
+}
+
+pub fn split_archive_path(image: &str) -> Option<(&str, &str)> {
+    image.split_once(ARCHIVE_SEPARATOR)
+}
+
+pub fn image_exists(image: &str) -> bool {
+    match split_archive_path(image) {
+        Some((archive, _)) => Path::new(archive).is_file(),
+        None => Path::new(image).is_file(),
+    }
+}
+
+pub fn read_images_in_archive(archive: &str) -> Result<Vec<String>, String> {
+    let file = match File::open(archive) {
+        Ok(value) => value,
+        Err(_) => return Err(format!("Failed to open archive {}", archive)),
+    };
+
+    let archive_file = match ZipArchive::new(file) {
+        Ok(value) => value,
+        Err(_) => return Err(format!("Failed to read archive {}", archive)),
+    };
+
+    let mut images: Vec<String> = archive_file
+        .file_names()
+        .filter(|name| !name.ends_with('/'))
+        .filter(|name| !name.starts_with("__MACOSX/"))
+        .filter(|name| !is_sidecar(Path::new(name)))
+        .map(|name| format!("{}{}{}", archive, ARCHIVE_SEPARATOR, name))
+        .collect();
+
+    images.sort();
+
+    Ok(images)
+}
+
+pub fn read_archive_image(image: &str) -> Result<Vec<u8>, String> {
+    let (archive, name) = match split_archive_path(image) {
+        Some(value) => value,
+        None => return Err(format!("{} is not inside an archive", image)),
+    };
+
+    let file = match File::open(archive) {
+        Ok(value) => value,
+        Err(_) => return Err(format!("Failed to open archive {}", archive)),
+    };
+
+    let mut archive_file = match ZipArchive::new(file) {
+        Ok(value) => value,
+        Err(_) => return Err(format!("Failed to read archive {}", archive)),
+    };
+
+    let mut entry = match archive_file.by_name(name) {
+        Ok(value) => value,
+        Err(_) => return Err(format!("Failed to find {} in archive {}", name, archive)),
+    };
+
+    let mut bytes = vec![];
+    if entry.read_to_end(&mut bytes).is_err() {
+        return Err(format!("Failed to read {} in archive {}", name, archive));
+    }
+
+    Ok(bytes)
+}
@@ -0,0 +1,54 @@
+use crate::settings::Settings;
+
+pub fn apply_arguments<I: Iterator<Item = String>>(settings: &mut Settings, arguments: I) {
+    let mut arguments = arguments;
+
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "--seed" => {
+                settings.seed = match arguments.next() {
+                    Some(value) => parse_seed(&value),
+                    None => None,
+                };
+            },
+            _ => eprintln!("Unknown argument: {}", argument),
+        }
+    }
+}
+
+pub fn parse_seed(value: &str) -> Option<u64> {
+    value.trim().parse::<u64>().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::generate_seed;
+
+    #[test]
+    fn parse_seed_round_trips_generated_seeds() {
+        for _ in 0..100 {
+            let seed = generate_seed();
+            assert_eq!(parse_seed(&seed.to_string()), Some(seed));
+        }
+    }
+
+    #[test]
+    fn parse_seed_ignores_surrounding_whitespace() {
+        assert_eq!(parse_seed("  42\n"), Some(42));
+    }
+
+    #[test]
+    fn parse_seed_rejects_invalid_values() {
+        assert_eq!(parse_seed(""), None);
+        assert_eq!(parse_seed("abc"), None);
+        assert_eq!(parse_seed("-1"), None);
+    }
+
+    #[test]
+    fn seed_argument_is_applied() {
+        let mut settings = Settings::new();
+        apply_arguments(&mut settings, ["--seed".to_owned(), "1234".to_owned()].into_iter());
+        assert_eq!(settings.seed, Some(1234));
+    }
+}
@@ -0,0 +1,46 @@
+use gtk::gdk_pixbuf::{InterpType, Pixbuf};
+
+pub fn flip_drawing(drawing: &Pixbuf, flipped: bool) -> Pixbuf {
+    match flipped {
+        true => drawing.flip(true).unwrap_or_else(|| drawing.clone()),
+        false => drawing.clone(),
+    }
+}
+
+pub fn overlay_drawing(reference: &Pixbuf, drawing: &Pixbuf, opacity: f64, offset_x: i32, offset_y: i32, scale: f64) -> Option<Pixbuf> {
+    let target = reference.add_alpha(false, 0, 0, 0)?;
+
+    let width = reference.width() as f64;
+    let height = reference.height() as f64;
+
+    let fit = f64::min(width / drawing.width() as f64, height / drawing.height() as f64) * scale;
+    let x = (width - drawing.width() as f64 * fit) / 2.0 + offset_x as f64;
+    let y = (height - drawing.height() as f64 * fit) / 2.0 + offset_y as f64;
+
+    let left = x.floor().max(0.0) as i32;
+    let top = y.floor().max(0.0) as i32;
+    let right = (x + drawing.width() as f64 * fit).ceil().min(width) as i32;
+    let bottom = (y + drawing.height() as f64 * fit).ceil().min(height) as i32;
+
+    if right <= left || bottom <= top {
+        return Some(target);
+    }
+
+    let alpha = (opacity.clamp(0.0, 1.0) * 255.0).round() as i32;
+
+    drawing.composite(
+        &target,
+        left,
+        top,
+        right - left,
+        bottom - top,
+        x,
+        y,
+        fit,
+        fit,
+        InterpType::Bilinear,
+        alpha,
+    );
+
+    Some(target)
+}
@@ -2,7 +2,13 @@ mod settings_page;
 mod session_page;
 mod error_page;
 mod complete_page;
+mod library_page;
+mod loading_page;
+mod gallery_page;
 mod folder_picker;
+mod list_file_picker;
+mod list_file_saver;
+mod drawing_picker;
 mod session_timer;
 
 pub use {
@@ -10,6 +16,12 @@ pub use {
     session_page::*,
     error_page::*,
     complete_page::*,
+    library_page::*,
+    loading_page::*,
+    gallery_page::*,
     folder_picker::*,
+    list_file_picker::*,
+    list_file_saver::*,
+    drawing_picker::*,
     session_timer::*,
 };
\ No newline at end of file
@@ -1,33 +1,339 @@
 use gtk::prelude::*;
+use gtk::gdk_pixbuf::Pixbuf;
+use relm4::factory::{FactoryPrototype, FactoryVec};
 use relm4::*;
 
-use crate::app::{App, AppMsg};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use crate::{
+    app::{App, AppMsg},
+    history::{History, SessionRecord},
+    session::Session,
+    settings::format_duration,
+    source::save_image_list,
+    thumbnails::{ensure_thumbnail, THUMBNAIL_SIZE},
+    components::*,
+};
+
+pub struct CompletePageComponents {
+    list_file_saver: RelmComponent<ListFileSaver, CompletePage>,
+    drawing_picker: RelmComponent<DrawingPicker, CompletePage>,
+}
+
+impl Components<CompletePage> for CompletePageComponents {
+    fn init_components(parent_model: &CompletePage, parent_sender: Sender<CompletePageMsg>) -> Self {
+        Self {
+            list_file_saver: RelmComponent::new(parent_model, parent_sender.clone()),
+            drawing_picker: RelmComponent::new(parent_model, parent_sender.clone()),
+        }
+    }
+
+    fn connect_parent(&mut self, parent_widgets: &<CompletePage as Model>::Widgets) {
+        self.list_file_saver.connect_parent(parent_widgets);
+        self.drawing_picker.connect_parent(parent_widgets);
+    }
+}
+
+pub struct DrawingPairItem {
+    reference: String,
+    name: String,
+    drawing: Option<String>,
+    reference_thumbnail: Option<Pixbuf>,
+    drawing_thumbnail: Option<Pixbuf>,
+}
+
+impl DrawingPairItem {
+    fn get_attach_text(&self) -> String {
+        match self.drawing {
+            Some(_) => "Replace drawing".into(),
+            None => "Attach drawing".into(),
+        }
+    }
+}
+
+#[relm4::factory_prototype(pub)]
+impl FactoryPrototype for DrawingPairItem {
+    type Factory = FactoryVec<Self>;
+    type Widgets = DrawingPairItemWidgets;
+    type View = gtk::Box;
+    type Msg = CompletePageMsg;
+
+    view! {
+        gtk::Box {
+            set_spacing: 10,
+
+            append = &gtk::Picture {
+                set_width_request: THUMBNAIL_SIZE,
+                set_height_request: THUMBNAIL_SIZE,
+                set_tooltip_text: Some(self.reference.as_str()),
+                set_pixbuf: watch!(self.reference_thumbnail.as_ref()),
+            },
+
+            append = &gtk::Picture {
+                set_width_request: THUMBNAIL_SIZE,
+                set_height_request: THUMBNAIL_SIZE,
+                set_tooltip_text: watch!(self.drawing.as_deref()),
+                set_pixbuf: watch!(self.drawing_thumbnail.as_ref()),
+            },
+
+            append = &gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+                set_valign: gtk::Align::Center,
+                set_spacing: 5,
+
+                append = &gtk::Label {
+                    set_ellipsize: gtk::pango::EllipsizeMode::Middle,
+                    set_max_width_chars: 20,
+                    set_halign: gtk::Align::Start,
+                    set_label: &self.name,
+                },
+
+                append = &gtk::Button {
+                    set_label: watch!(&self.get_attach_text()),
+                    connect_clicked(sender, key) => move |_| {
+                        send!(sender, CompletePageMsg::AttachDrawing(key));
+                    },
+                },
+            },
+        }
+    }
+
+    fn position(&self, _index: &usize) {}
+}
 
 pub struct CompletePage {
+    session: Session,
+    record: Option<SessionRecord>,
+    pairs: FactoryVec<DrawingPairItem>,
+    thumbnails: HashMap<String, Pixbuf>,
+    attaching: Option<usize>,
+}
+
+impl CompletePage {
+    fn save_record(&self) {
+        if let Some(record) = &self.record {
+            let mut history = History::load();
+            history.record(record.clone());
+            history.save();
+        }
+    }
+
+    fn get_drawings_title(&self) -> String {
+        let attached = self.pairs
+            .iter()
+            .filter(|item| item.drawing.is_some())
+            .count();
+
+        format!("Drawings ({} of {} attached)", attached, self.pairs.len())
+    }
+
+    fn get_seed_text(&self) -> String {
+        format!("Session code: {}", self.session.seed)
+    }
+
+    fn get_images_done_text(&self) -> String {
+        format!("{} images drawn", self.session.images_done())
+    }
+
+    fn get_time_spent_title(&self) -> String {
+        format!("Time spent ({})", format_duration(Duration::from_secs(self.session.total_time_spent() as u64)))
+    }
+
+    fn get_time_spent_text(&self) -> String {
+        self.session.images
+            .iter()
+            .zip(self.session.time_spent.iter())
+            .enumerate()
+            .map(|(index, (image, seconds))| {
+                let name = match Path::new(image).file_name().and_then(|value| value.to_str()) {
+                    Some(value) => value,
+                    None => image,
+                };
+
+                let time = format_duration(Duration::from_secs(*seconds as u64));
+
+                match self.session.image_extension(index) {
+                    0 => format!("{}\t{}", time, name),
+                    extension => format!("{} (+{})\t{}", time, format_duration(Duration::from_secs(extension as u64)), name),
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn get_duplicates_title(&self) -> String {
+        format!(
+            "Skipped duplicates ({} groups among the {} images scanned)",
+            self.session.duplicates.len(),
+            self.session.duplicates_scanned,
+        )
+    }
+
+    fn get_duplicates_text(&self) -> String {
+        self.session.duplicates
+            .iter()
+            .map(|cluster| cluster.join("\n"))
+            .collect::<Vec<String>>()
+            .join("\n\n")
+    }
 }
 
 pub enum CompletePageMsg {
+    SessionCompleted(Session),
+    SelectListFile,
+    SaveList(String),
+    AttachDrawing(usize),
+    DrawingSelected(String),
+    ThumbnailReady(String, PathBuf),
+    ShowGallery,
 }
 
 impl Model for CompletePage {
     type Msg = CompletePageMsg;
     type Widgets = CompletePageWidgets;
-    type Components = ();
+    type Components = CompletePageComponents;
+}
+
+impl DrawingPickerParent for CompletePage {
+    fn drawing_selected(file: String) -> CompletePageMsg {
+        CompletePageMsg::DrawingSelected(file)
+    }
 }
 
 impl ComponentUpdate<App> for CompletePage {
     fn init_model(_parent_model: &App) -> Self {
         Self {
-
+            session: Session::new(),
+            record: None,
+            pairs: FactoryVec::new(),
+            thumbnails: HashMap::new(),
+            attaching: None,
         }
     }
 
-    fn update(&mut self, msg: CompletePageMsg, _components: &(), _sender: Sender<CompletePageMsg>, _parent_sender: Sender<AppMsg>) {
+    fn update(&mut self, msg: CompletePageMsg, components: &CompletePageComponents, sender: Sender<CompletePageMsg>, parent_sender: Sender<AppMsg>) {
         match msg {
+            CompletePageMsg::SessionCompleted(session) => {
+                let record = SessionRecord::from_session(&session);
+                let mut missing_thumbnails = vec![];
+
+                self.pairs.clear();
+                self.attaching = None;
+
+                for image in session.images.iter().take(session.images_done()) {
+                    let drawing = record.drawing(image).map(|value| value.to_owned());
+
+                    for path in [Some(image), drawing.as_ref()].into_iter().flatten() {
+                        if !self.thumbnails.contains_key(path) {
+                            missing_thumbnails.push(path.clone());
+                        }
+                    }
+
+                    self.pairs.push(DrawingPairItem {
+                        reference: image.clone(),
+                        name: image_name(image),
+                        reference_thumbnail: self.thumbnails.get(image).cloned(),
+                        drawing_thumbnail: drawing.as_ref().and_then(|value| self.thumbnails.get(value).cloned()),
+                        drawing,
+                    });
+                }
+
+                self.session = session;
+                self.record = Some(record);
+                self.save_record();
+
+                generate_thumbnails(missing_thumbnails, sender);
+            },
+            CompletePageMsg::SelectListFile => {
+                components.list_file_saver.send(ListFileSaverMsg::Show).unwrap();
+            },
+            CompletePageMsg::SaveList(file) => {
+                if save_image_list(&file, &self.session.images).is_err() {
+                    send!(parent_sender, AppMsg::ShowError("Failed to save image list".into()));
+                }
+            },
+            CompletePageMsg::AttachDrawing(index) => {
+                self.attaching = Some(index);
+                components.drawing_picker.send(DrawingPickerMsg::Show).unwrap();
+            },
+            CompletePageMsg::DrawingSelected(drawing) => {
+                let reference = match self.attaching.take().and_then(|index| self.pairs.get(index)) {
+                    Some(item) => item.reference.clone(),
+                    None => return,
+                };
+
+                if let Some(record) = &mut self.record {
+                    record.attach_drawing(&reference, &drawing);
+                }
+
+                self.save_record();
+
+                let thumbnail = self.thumbnails.get(&drawing).cloned();
+
+                for index in 0..self.pairs.len() {
+                    if let Some(item) = self.pairs.get_mut(index) {
+                        if item.reference == reference {
+                            item.drawing = Some(drawing.clone());
+                            item.drawing_thumbnail = thumbnail.clone();
+                        }
+                    }
+                }
+
+                if thumbnail.is_none() {
+                    generate_thumbnails(vec![drawing], sender);
+                }
+            },
+            CompletePageMsg::ThumbnailReady(image, thumbnail) => {
+                let thumbnail = match Pixbuf::from_file(thumbnail) {
+                    Ok(value) => value,
+                    Err(_) => return,
+                };
+
+                for index in 0..self.pairs.len() {
+                    if let Some(item) = self.pairs.get_mut(index) {
+                        if item.reference == image {
+                            item.reference_thumbnail = Some(thumbnail.clone());
+                        }
+
+                        if item.drawing.as_ref() == Some(&image) {
+                            item.drawing_thumbnail = Some(thumbnail.clone());
+                        }
+                    }
+                }
+
+                self.thumbnails.insert(image, thumbnail);
+            },
+            CompletePageMsg::ShowGallery => {
+                send!(parent_sender, AppMsg::ShowGallery);
+            },
         }
     }
 }
 
+fn generate_thumbnails(images: Vec<String>, sender: Sender<CompletePageMsg>) {
+    if images.is_empty() {
+        return;
+    }
+
+    thread::spawn(move || {
+        for image in images {
+            if let Some(thumbnail) = ensure_thumbnail(&image) {
+                send!(sender, CompletePageMsg::ThumbnailReady(image, thumbnail));
+            }
+        }
+    });
+}
+
+fn image_name(image: &str) -> String {
+    match Path::new(image).file_name().and_then(|value| value.to_str()) {
+        Some(value) => value.to_owned(),
+        None => image.to_owned(),
+    }
+}
+
 #[relm4::widget(pub)]
 impl Widgets<CompletePage, App> for CompletePageWidgets {
     view! {
@@ -45,6 +351,84 @@ impl Widgets<CompletePage, App> for CompletePageWidgets {
             append = &gtk::Label {
                 set_label: "Session complete",
             },
+
+            append = &gtk::Label {
+                set_label: watch!(&model.get_images_done_text()),
+            },
+
+            append = &gtk::Label {
+                set_selectable: true,
+                set_label: watch!(&model.get_seed_text()),
+            },
+
+            append = &gtk::Expander {
+                set_visible: watch!(!model.session.time_spent.is_empty()),
+                set_label: watch!(Some(model.get_time_spent_title().as_str())),
+
+                set_child = Some(&gtk::ScrolledWindow) {
+                    set_min_content_height: 150,
+                    set_min_content_width: 400,
+
+                    set_child = Some(&gtk::Label) {
+                        set_selectable: true,
+                        set_halign: gtk::Align::Start,
+                        set_label: watch!(&model.get_time_spent_text()),
+                    },
+                },
+            },
+
+            append = &gtk::Expander {
+                set_visible: watch!(!model.pairs.is_empty()),
+                set_expanded: true,
+                set_label: watch!(Some(model.get_drawings_title().as_str())),
+
+                set_child = Some(&gtk::ScrolledWindow) {
+                    set_min_content_height: 300,
+                    set_min_content_width: 400,
+
+                    set_child = Some(&gtk::Box) {
+                        set_orientation: gtk::Orientation::Vertical,
+                        set_spacing: 10,
+                        factory!(model.pairs),
+                    },
+                },
+            },
+
+            append = &gtk::Expander {
+                set_visible: watch!(!model.session.duplicates.is_empty()),
+                set_label: watch!(Some(model.get_duplicates_title().as_str())),
+
+                set_child = Some(&gtk::ScrolledWindow) {
+                    set_min_content_height: 150,
+                    set_min_content_width: 400,
+
+                    set_child = Some(&gtk::Label) {
+                        set_selectable: true,
+                        set_halign: gtk::Align::Start,
+                        set_label: watch!(&model.get_duplicates_text()),
+                    },
+                },
+            },
+
+            append = &gtk::Box {
+                set_spacing: 10,
+                set_homogeneous: true,
+
+                append = &gtk::Button {
+                    set_label: "Save image list",
+                    connect_clicked(sender) => move |_| {
+                        send!(sender, CompletePageMsg::SelectListFile);
+                    },
+                },
+
+                append = &gtk::Button {
+                    set_label: "Open gallery",
+                    set_tooltip_text: Some("Browse drawings from past sessions"),
+                    connect_clicked(sender) => move |_| {
+                        send!(sender, CompletePageMsg::ShowGallery);
+                    },
+                },
+            },
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,84 @@
+use gtk::prelude::*;
+use relm4::*;
+
+pub trait DrawingPickerParent: Model {
+    fn drawing_selected(file: String) -> Self::Msg;
+}
+
+pub struct DrawingPicker {
+    visible: bool,
+}
+
+pub enum DrawingPickerMsg {
+    Show,
+    Close,
+    SelectFile(String),
+}
+
+impl<ParentModel> ComponentUpdate<ParentModel> for DrawingPicker
+where
+    ParentModel: DrawingPickerParent,
+{
+    fn init_model(_parent_model: &ParentModel) -> Self {
+        Self { 
+            visible: false,
+        }
+    }
+
+    fn update(&mut self, msg: DrawingPickerMsg, _components: &(), _sender: Sender<DrawingPickerMsg>, parent_sender: Sender<ParentModel::Msg>) {
+        match msg {
+            DrawingPickerMsg::Show => {
+                self.visible = true;
+            },
+            DrawingPickerMsg::Close => {
+                self.visible = false;
+            },
+            DrawingPickerMsg::SelectFile(file) => {
+                self.visible = false;
+                send!(parent_sender, ParentModel::drawing_selected(file));
+            }
+        }
+    }
+}
+
+impl Model for DrawingPicker {
+    type Msg = DrawingPickerMsg;
+    type Widgets = DrawingPickerWidgets;
+    type Components = ();
+}
+
+#[relm4::widget(pub)]
+impl<ParentModel> Widgets<DrawingPicker, ParentModel> for DrawingPickerWidgets
+where
+    ParentModel: Model,
+{
+    view! {
+        file_chooser = gtk::FileChooserNative {
+            set_modal: true,
+            set_title: "Select drawing",
+            set_action: gtk::FileChooserAction::Open,
+            set_visible: watch!(model.visible),
+
+            connect_response(sender) => move |dialog, response| {
+                if response == gtk::ResponseType::Accept {
+                    if let Some(file) = dialog.file() {
+                        if let Some(file) = file.path() {
+                            if let Some(file) = file.to_str() {
+                                send!(sender, DrawingPickerMsg::SelectFile(file.into()));
+                            }
+                        }
+                    }
+                }
+
+                send!(sender, DrawingPickerMsg::Close);
+            }
+        }
+    }
+
+    fn post_init() {
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some("Images"));
+        filter.add_pixbuf_formats();
+        file_chooser.add_filter(&filter);
+    }
+}
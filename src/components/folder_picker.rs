@@ -5,10 +5,12 @@ use super::{SettingsPage, SettingsPageMsg};
 
 pub struct FolderPicker {
     visible: bool,
+    action: gtk::FileChooserAction,
 }
 
 pub enum FolderPickerMsg {
     Show,
+    ShowArchive,
     Close,
     SelectFolder(String),
 }
@@ -17,12 +19,18 @@ impl ComponentUpdate<SettingsPage> for FolderPicker {
     fn init_model(_parent_model: &SettingsPage) -> Self {
         Self { 
             visible: false,
+            action: gtk::FileChooserAction::SelectFolder,
         }
     }
 
     fn update(&mut self, msg: FolderPickerMsg, _components: &(), _sender: Sender<FolderPickerMsg>, parent_sender: Sender<SettingsPageMsg>) {
         match msg {
             FolderPickerMsg::Show => {
+                self.action = gtk::FileChooserAction::SelectFolder;
+                self.visible = true;
+            },
+            FolderPickerMsg::ShowArchive => {
+                self.action = gtk::FileChooserAction::Open;
                 self.visible = true;
             },
             FolderPickerMsg::Close => {
@@ -45,13 +53,20 @@ impl Model for FolderPicker {
 #[relm4::widget(pub)]
 impl Widgets<FolderPicker, SettingsPage> for FolderPickerWidgets {
     view! {
-        gtk::FileChooserNative {
+        file_chooser = gtk::FileChooserNative {
             set_modal: true,
-            set_action: gtk::FileChooserAction::SelectFolder,
+            set_action: watch!(model.action),
             set_visible: watch!(model.visible),
 
             connect_response(sender) => move |dialog, response| {
-                if response == gtk::ResponseType::Accept {
+                if response == gtk::ResponseType::Accept && dialog.action() == gtk::FileChooserAction::Open {
+                    if let Some(archive) = dialog.file() {
+                        let archive = archive.path().unwrap();
+                        let archive = archive.to_str().unwrap();
+                        send!(sender, FolderPickerMsg::SelectFolder(archive.into()));
+                    }
+                }
+                else if response == gtk::ResponseType::Accept {
                     if let Some(folder) = dialog.current_folder() {
                         let folder = folder.path().unwrap();
                         let folder = folder.to_str().unwrap();
@@ -63,4 +78,12 @@ impl Widgets<FolderPicker, SettingsPage> for FolderPickerWidgets {
             }
         }
     }
+
+    fn post_init() {
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some("Archives"));
+        filter.add_pattern("*.zip");
+        filter.add_pattern("*.cbz");
+        file_chooser.add_filter(&filter);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,270 @@
+use gtk::prelude::*;
+use gtk::gdk_pixbuf::Pixbuf;
+use gtk::glib;
+use relm4::factory::{FactoryPrototype, FactoryVec};
+use relm4::*;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crate::{
+    app::{App, AppMsg},
+    history::History,
+    settings::format_duration,
+    thumbnails::{ensure_thumbnail, THUMBNAIL_SIZE},
+};
+
+pub struct GalleryItem {
+    reference: String,
+    drawing: String,
+    date: String,
+    reference_thumbnail: Option<Pixbuf>,
+    drawing_thumbnail: Option<Pixbuf>,
+    show_reference: bool,
+}
+
+#[relm4::factory_prototype(pub)]
+impl FactoryPrototype for GalleryItem {
+    type Factory = FactoryVec<Self>;
+    type Widgets = GalleryItemWidgets;
+    type View = gtk::FlowBox;
+    type Msg = GalleryPageMsg;
+
+    view! {
+        gtk::Box {
+            set_orientation: gtk::Orientation::Vertical,
+            set_spacing: 5,
+
+            append = &gtk::Box {
+                set_spacing: 5,
+                set_halign: gtk::Align::Center,
+
+                append = &gtk::Picture {
+                    set_visible: watch!(self.show_reference),
+                    set_width_request: THUMBNAIL_SIZE,
+                    set_height_request: THUMBNAIL_SIZE,
+                    set_tooltip_text: Some(self.reference.as_str()),
+                    set_pixbuf: watch!(self.reference_thumbnail.as_ref()),
+                },
+
+                append = &gtk::Picture {
+                    set_width_request: THUMBNAIL_SIZE,
+                    set_height_request: THUMBNAIL_SIZE,
+                    set_tooltip_text: Some(self.drawing.as_str()),
+                    set_pixbuf: watch!(self.drawing_thumbnail.as_ref()),
+                },
+            },
+
+            append = &gtk::Label {
+                set_label: &self.date,
+            },
+        }
+    }
+
+    fn position(&self, _index: &usize) {}
+}
+
+pub struct GalleryPage {
+    items: FactoryVec<GalleryItem>,
+    thumbnails: HashMap<String, Pixbuf>,
+    sessions: usize,
+    time_spent: usize,
+    newest_first: bool,
+    show_references: bool,
+    generation: Arc<AtomicU64>,
+}
+
+impl GalleryPage {
+    fn load_items(&mut self, sender: Sender<GalleryPageMsg>) {
+        let history = History::load();
+
+        let mut drawings = history.drawings();
+        if self.newest_first {
+            drawings.reverse();
+        }
+
+        self.sessions = history.records().len();
+        self.time_spent = history.records()
+            .iter()
+            .map(|record| record.time_spent)
+            .sum();
+        self.items.clear();
+
+        let mut missing_thumbnails = vec![];
+
+        for (record, pair) in drawings {
+            for path in [&pair.reference, &pair.drawing] {
+                if !self.thumbnails.contains_key(path) && !missing_thumbnails.contains(path) {
+                    missing_thumbnails.push(path.clone());
+                }
+            }
+
+            self.items.push(GalleryItem {
+                reference: pair.reference.clone(),
+                drawing: pair.drawing.clone(),
+                date: format_date(record.finished),
+                reference_thumbnail: self.thumbnails.get(&pair.reference).cloned(),
+                drawing_thumbnail: self.thumbnails.get(&pair.drawing).cloned(),
+                show_reference: self.show_references,
+            });
+        }
+
+        self.generate_thumbnails(missing_thumbnails, sender);
+    }
+
+    fn generate_thumbnails(&self, images: Vec<String>, sender: Sender<GalleryPageMsg>) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let current_generation = self.generation.clone();
+
+        thread::spawn(move || {
+            for image in images {
+                if current_generation.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+
+                if let Some(thumbnail) = ensure_thumbnail(&image) {
+                    send!(sender, GalleryPageMsg::ThumbnailReady(image, thumbnail));
+                }
+            }
+        });
+    }
+
+    fn get_status_text(&self) -> String {
+        format!(
+            "{} drawings from {} sessions, {} of practice",
+            self.items.len(),
+            self.sessions,
+            format_duration(Duration::from_secs(self.time_spent as u64)),
+        )
+    }
+}
+
+pub enum GalleryPageMsg {
+    Open,
+    NewestFirst(bool),
+    ShowReferences(bool),
+    ThumbnailReady(String, PathBuf),
+}
+
+impl Model for GalleryPage {
+    type Msg = GalleryPageMsg;
+    type Widgets = GalleryPageWidgets;
+    type Components = ();
+}
+
+impl ComponentUpdate<App> for GalleryPage {
+    fn init_model(_parent_model: &App) -> Self {
+        Self {
+            items: FactoryVec::new(),
+            thumbnails: HashMap::new(),
+            sessions: 0,
+            time_spent: 0,
+            newest_first: false,
+            show_references: true,
+            generation: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    fn update(&mut self, msg: GalleryPageMsg, _components: &(), sender: Sender<GalleryPageMsg>, _parent_sender: Sender<AppMsg>) {
+        match msg {
+            GalleryPageMsg::Open => {
+                self.load_items(sender);
+            },
+            GalleryPageMsg::NewestFirst(value) => {
+                self.newest_first = value;
+                self.load_items(sender);
+            },
+            GalleryPageMsg::ShowReferences(value) => {
+                self.show_references = value;
+
+                for index in 0..self.items.len() {
+                    if let Some(item) = self.items.get_mut(index) {
+                        item.show_reference = value;
+                    }
+                }
+            },
+            GalleryPageMsg::ThumbnailReady(image, thumbnail) => {
+                let thumbnail = match Pixbuf::from_file(thumbnail) {
+                    Ok(value) => value,
+                    Err(_) => return,
+                };
+
+                for index in 0..self.items.len() {
+                    if let Some(item) = self.items.get_mut(index) {
+                        if item.reference == image {
+                            item.reference_thumbnail = Some(thumbnail.clone());
+                        }
+
+                        if item.drawing == image {
+                            item.drawing_thumbnail = Some(thumbnail.clone());
+                        }
+                    }
+                }
+
+                self.thumbnails.insert(image, thumbnail);
+            },
+        }
+    }
+}
+
+fn format_date(timestamp: u64) -> String {
+    match glib::DateTime::from_unix_local(timestamp as i64).and_then(|date| date.format("%x %H:%M")) {
+        Ok(value) => value.to_string(),
+        Err(_) => "".into(),
+    }
+}
+
+#[relm4::widget(pub)]
+impl Widgets<GalleryPage, App> for GalleryPageWidgets {
+    view! {
+        gtk::Box {
+            set_orientation: gtk::Orientation::Vertical,
+
+            append = &gtk::ActionBar {
+                pack_start = &gtk::ToggleButton {
+                    set_label: "Newest first",
+                    set_active: false,
+                    connect_toggled(sender) => move |button| {
+                        send!(sender, GalleryPageMsg::NewestFirst(button.is_active()));
+                    },
+                },
+
+                pack_start = &gtk::ToggleButton {
+                    set_label: "Show references",
+                    set_active: true,
+                    connect_toggled(sender) => move |button| {
+                        send!(sender, GalleryPageMsg::ShowReferences(button.is_active()));
+                    },
+                },
+            },
+
+            append = &gtk::ScrolledWindow {
+                set_vexpand: true,
+                set_hexpand: true,
+
+                set_child = Some(&gtk::FlowBox) {
+                    set_valign: gtk::Align::Start,
+                    set_selection_mode: gtk::SelectionMode::None,
+                    set_homogeneous: true,
+                    set_row_spacing: 10,
+                    set_column_spacing: 10,
+                    set_margin_top: 10,
+                    set_margin_bottom: 10,
+                    set_margin_start: 10,
+                    set_margin_end: 10,
+                    factory!(model.items),
+                },
+            },
+
+            append = &gtk::Label {
+                set_margin_top: 5,
+                set_margin_bottom: 5,
+                set_label: watch!(&model.get_status_text()),
+            },
+        }
+    }
+}
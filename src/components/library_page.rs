@@ -0,0 +1,499 @@
+use gtk::prelude::*;
+use gtk::gdk_pixbuf::Pixbuf;
+use relm4::factory::{FactoryPrototype, FactoryVec};
+use relm4::*;
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use crate::{
+    app::{App, AppMsg},
+    image_list::{ImageList, FAVOURITES_FILE},
+    session::Session,
+    settings::Settings,
+    tags::{normalize_tag, TagStore},
+    thumbnails::{ensure_thumbnail, THUMBNAIL_SIZE},
+};
+
+const MAX_VISIBLE_IMAGES: usize = 500;
+
+#[derive(Clone)]
+pub struct LibraryImage {
+    path: String,
+    name: String,
+    tags: Vec<String>,
+}
+
+pub struct LibraryItem {
+    path: String,
+    name: String,
+    selected: bool,
+    favourite: bool,
+    tagged: bool,
+    tag: String,
+    thumbnail: Option<Pixbuf>,
+}
+
+impl LibraryItem {
+    fn get_favourite_icon(&self) -> String {
+        match self.favourite {
+            true => "starred-symbolic".into(),
+            false => "non-starred-symbolic".into(),
+        }
+    }
+}
+
+#[relm4::factory_prototype(pub)]
+impl FactoryPrototype for LibraryItem {
+    type Factory = FactoryVec<Self>;
+    type Widgets = LibraryItemWidgets;
+    type View = gtk::FlowBox;
+    type Msg = LibraryPageMsg;
+
+    view! {
+        gtk::Box {
+            set_orientation: gtk::Orientation::Vertical,
+            set_spacing: 5,
+
+            append = &gtk::Picture {
+                set_width_request: THUMBNAIL_SIZE,
+                set_height_request: THUMBNAIL_SIZE,
+                set_pixbuf: watch!(self.thumbnail.as_ref()),
+            },
+
+            append = &gtk::Label {
+                set_ellipsize: gtk::pango::EllipsizeMode::Middle,
+                set_max_width_chars: 15,
+                set_tooltip_text: Some(self.path.as_str()),
+                set_label: &self.name,
+            },
+
+            append = &gtk::Box {
+                set_halign: gtk::Align::Center,
+
+                append = &gtk::CheckButton {
+                    set_active: watch!(self.selected),
+                    connect_toggled(sender, key) => move |check_button| {
+                        send!(sender, LibraryPageMsg::Select(key, check_button.is_active()));
+                    },
+                },
+
+                append = &gtk::Button {
+                    set_icon_name: watch!(&self.get_favourite_icon()),
+                    set_has_frame: false,
+                    connect_clicked(sender, key) => move |_| {
+                        send!(sender, LibraryPageMsg::ToggleFavourite(key));
+                    },
+                },
+
+                append = &gtk::ToggleButton {
+                    set_visible: watch!(!self.tag.is_empty()),
+                    set_label: watch!(&self.tag),
+                    set_has_frame: false,
+                    set_active: watch!(self.tagged),
+                    connect_toggled(sender, key) => move |button| {
+                        send!(sender, LibraryPageMsg::Tag(key, button.is_active()));
+                    },
+                },
+            },
+        }
+    }
+
+    fn position(&self, _index: &usize) {}
+}
+
+pub struct LibraryPage {
+    settings: Settings,
+    images: Vec<LibraryImage>,
+    items: FactoryVec<LibraryItem>,
+    thumbnails: HashMap<String, Pixbuf>,
+    selected: HashSet<String>,
+    favourites: ImageList,
+    search: String,
+    tag: String,
+    favourites_only: bool,
+    matches: usize,
+    loading: bool,
+    load_generation: u64,
+    generation: Arc<AtomicU64>,
+}
+
+impl LibraryPage {
+    fn filter_images(&mut self, sender: Sender<LibraryPageMsg>) {
+        let search = self.search.trim().to_lowercase();
+
+        let matching: Vec<&LibraryImage> = self.images
+            .iter()
+            .filter(|image| !self.favourites_only || self.favourites.contains(&image.path))
+            .filter(|image| {
+                search.is_empty()
+                    || image.name.to_lowercase().contains(&search)
+                    || image.tags.iter().any(|tag| tag.contains(&search))
+            })
+            .collect();
+
+        self.matches = matching.len();
+        self.items.clear();
+
+        let mut missing_thumbnails = vec![];
+
+        for image in matching.into_iter().take(MAX_VISIBLE_IMAGES) {
+            let thumbnail = self.thumbnails.get(&image.path).cloned();
+
+            if thumbnail.is_none() {
+                missing_thumbnails.push(image.path.clone());
+            }
+
+            self.items.push(LibraryItem {
+                path: image.path.clone(),
+                name: image.name.clone(),
+                selected: self.selected.contains(&image.path),
+                favourite: self.favourites.contains(&image.path),
+                tagged: image.tags.contains(&self.tag),
+                tag: self.tag.clone(),
+                thumbnail,
+            });
+        }
+
+        self.generate_thumbnails(missing_thumbnails, sender);
+    }
+
+    fn generate_thumbnails(&self, images: Vec<String>, sender: Sender<LibraryPageMsg>) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let current_generation = self.generation.clone();
+
+        thread::spawn(move || {
+            for image in images {
+                if current_generation.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+
+                if let Some(thumbnail) = ensure_thumbnail(&image) {
+                    send!(sender, LibraryPageMsg::ThumbnailReady(image, thumbnail));
+                }
+            }
+        });
+    }
+
+    fn load_images(&mut self, settings: Settings, sender: Sender<LibraryPageMsg>) {
+        self.load_generation += 1;
+        self.loading = true;
+
+        let generation = self.load_generation;
+        thread::spawn(move || {
+            let images = Session::pool(&settings).map(|images| {
+                let tags = TagStore::load();
+
+                let mut images: Vec<LibraryImage> = images
+                    .into_iter()
+                    .map(|path| LibraryImage {
+                        name: image_name(&path),
+                        tags: tags.image_tags(&path),
+                        path,
+                    })
+                    .collect();
+
+                images.sort_by(|a, b| a.name.cmp(&b.name));
+                images
+            });
+
+            send!(sender, LibraryPageMsg::Loaded(generation, images));
+        });
+    }
+
+    fn get_status_text(&self) -> String {
+        if self.loading {
+            return "Loading images".into();
+        }
+
+        let mut text = format!("{} of {} images", self.matches, self.images.len());
+
+        if self.matches > MAX_VISIBLE_IMAGES {
+            text = format!("{}, showing the first {}", text, MAX_VISIBLE_IMAGES);
+        }
+
+        text
+    }
+
+    fn get_start_text(&self) -> String {
+        format!("Start session with {} selected", self.selected.len())
+    }
+}
+
+pub enum LibraryPageMsg {
+    Open(Settings),
+    Loaded(u64, Result<Vec<LibraryImage>, String>),
+    SearchChanged(String),
+    FavouritesOnly(bool),
+    Select(usize, bool),
+    ToggleFavourite(usize),
+    TagChanged(String),
+    Tag(usize, bool),
+    SelectAll,
+    SelectNone,
+    ThumbnailReady(String, PathBuf),
+    Start,
+}
+
+impl Model for LibraryPage {
+    type Msg = LibraryPageMsg;
+    type Widgets = LibraryPageWidgets;
+    type Components = ();
+}
+
+impl ComponentUpdate<App> for LibraryPage {
+    fn init_model(parent_model: &App) -> Self {
+        Self {
+            settings: parent_model.settings.clone(),
+            images: vec![],
+            items: FactoryVec::new(),
+            thumbnails: HashMap::new(),
+            selected: HashSet::new(),
+            favourites: ImageList::load(FAVOURITES_FILE),
+            search: "".into(),
+            tag: "".into(),
+            favourites_only: false,
+            matches: 0,
+            loading: false,
+            load_generation: 0,
+            generation: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    fn update(&mut self, msg: LibraryPageMsg, _components: &(), sender: Sender<LibraryPageMsg>, parent_sender: Sender<AppMsg>) {
+        match msg {
+            LibraryPageMsg::Open(settings) => {
+                self.settings = settings.clone();
+                self.images.clear();
+                self.selected.clear();
+                self.filter_images(sender.clone());
+                self.load_images(settings, sender);
+            },
+            LibraryPageMsg::Loaded(generation, images) => {
+                if generation != self.load_generation {
+                    return;
+                }
+
+                self.loading = false;
+
+                self.images = match images {
+                    Ok(value) => value,
+                    Err(error) => {
+                        send!(parent_sender, AppMsg::ShowError(error));
+                        return;
+                    },
+                };
+
+                self.favourites = ImageList::load(FAVOURITES_FILE);
+                self.filter_images(sender);
+            },
+            LibraryPageMsg::SearchChanged(value) => {
+                self.search = value;
+                self.filter_images(sender);
+            },
+            LibraryPageMsg::FavouritesOnly(value) => {
+                self.favourites_only = value;
+                self.filter_images(sender);
+            },
+            LibraryPageMsg::Select(index, value) => {
+                if let Some(item) = self.items.get(index) {
+                    if item.selected == value {
+                        return;
+                    }
+                }
+
+                if let Some(item) = self.items.get_mut(index) {
+                    item.selected = value;
+
+                    match value {
+                        true => self.selected.insert(item.path.clone()),
+                        false => self.selected.remove(&item.path),
+                    };
+                }
+            },
+            LibraryPageMsg::ToggleFavourite(index) => {
+                if let Some(item) = self.items.get_mut(index) {
+                    item.favourite = self.favourites.toggle(&item.path);
+                    self.favourites.save();
+                }
+            },
+            LibraryPageMsg::TagChanged(value) => {
+                self.tag = normalize_tag(&value);
+
+                for index in 0..self.items.len() {
+                    if let Some(item) = self.items.get_mut(index) {
+                        let tagged = match self.images.iter().find(|image| image.path == item.path) {
+                            Some(image) => image.tags.contains(&self.tag),
+                            None => false,
+                        };
+
+                        item.tag = self.tag.clone();
+                        item.tagged = tagged;
+                    }
+                }
+            },
+            LibraryPageMsg::Tag(index, value) => {
+                if let Some(item) = self.items.get(index) {
+                    if item.tagged == value {
+                        return;
+                    }
+                }
+
+                if let Some(item) = self.items.get_mut(index) {
+                    let mut tags = TagStore::load();
+                    item.tagged = tags.toggle_tag(&item.path, &self.tag);
+                    tags.save();
+
+                    if let Some(image) = self.images.iter_mut().find(|image| image.path == item.path) {
+                        image.tags = tags.image_tags(&image.path);
+                    }
+                }
+            },
+            LibraryPageMsg::SelectAll => {
+                for index in 0..self.items.len() {
+                    if let Some(item) = self.items.get_mut(index) {
+                        item.selected = true;
+                        self.selected.insert(item.path.clone());
+                    }
+                }
+            },
+            LibraryPageMsg::SelectNone => {
+                self.selected.clear();
+
+                for index in 0..self.items.len() {
+                    if let Some(item) = self.items.get_mut(index) {
+                        item.selected = false;
+                    }
+                }
+            },
+            LibraryPageMsg::ThumbnailReady(image, thumbnail) => {
+                let thumbnail = match Pixbuf::from_file(thumbnail) {
+                    Ok(value) => value,
+                    Err(_) => return,
+                };
+
+                let index = self.items
+                    .iter()
+                    .position(|item| item.path == image);
+
+                if let Some(item) = index.and_then(|index| self.items.get_mut(index)) {
+                    item.thumbnail = Some(thumbnail.clone());
+                }
+
+                self.thumbnails.insert(image, thumbnail);
+            },
+            LibraryPageMsg::Start => {
+                let images: Vec<String> = self.images
+                    .iter()
+                    .filter(|image| self.selected.contains(&image.path))
+                    .map(|image| image.path.clone())
+                    .collect();
+
+                send!(parent_sender, AppMsg::StartSelectedSession(self.settings.clone(), images));
+            },
+        }
+    }
+}
+
+fn image_name(image: &str) -> String {
+    match Path::new(image).file_name().and_then(|value| value.to_str()) {
+        Some(value) => value.to_owned(),
+        None => image.to_owned(),
+    }
+}
+
+#[relm4::widget(pub)]
+impl Widgets<LibraryPage, App> for LibraryPageWidgets {
+    view! {
+        gtk::Box {
+            set_orientation: gtk::Orientation::Vertical,
+
+            append = &gtk::ActionBar {
+                pack_start = &gtk::SearchEntry {
+                    set_placeholder_text: Some("Search names and tags"),
+                    connect_search_changed(sender) => move |entry| {
+                        send!(sender, LibraryPageMsg::SearchChanged(entry.text().to_string()));
+                    },
+                },
+
+                pack_start = &gtk::Entry {
+                    set_placeholder_text: Some("Tag to toggle"),
+                    set_tooltip_text: Some("Show a toggle for this tag on every image"),
+                    connect_changed(sender) => move |entry| {
+                        send!(sender, LibraryPageMsg::TagChanged(entry.text().to_string()));
+                    },
+                },
+
+                pack_start = &gtk::ToggleButton {
+                    set_icon_name: "starred-symbolic",
+                    set_tooltip_text: Some("Only show favourites"),
+                    connect_toggled(sender) => move |button| {
+                        send!(sender, LibraryPageMsg::FavouritesOnly(button.is_active()));
+                    },
+                },
+
+                pack_end = &gtk::Button {
+                    set_label: watch!(&model.get_start_text()),
+                    set_sensitive: watch!(!model.selected.is_empty()),
+                    connect_clicked(sender) => move |_| {
+                        send!(sender, LibraryPageMsg::Start);
+                    },
+                },
+
+                pack_end = &gtk::MenuButton {
+                    set_icon_name: "view-more-horizontal-symbolic",
+                    set_has_frame: false,
+
+                    set_popover = Some(&gtk::Popover) {
+                        set_child = Some(&gtk::Box) {
+                            set_orientation: gtk::Orientation::Vertical,
+
+                            append = &gtk::Button {
+                                set_label: "Select all shown",
+                                set_has_frame: false,
+                                connect_clicked(sender) => move |_| {
+                                    send!(sender, LibraryPageMsg::SelectAll);
+                                },
+                            },
+
+                            append = &gtk::Button {
+                                set_label: "Select none",
+                                set_has_frame: false,
+                                connect_clicked(sender) => move |_| {
+                                    send!(sender, LibraryPageMsg::SelectNone);
+                                },
+                            },
+                        }
+                    }
+                },
+            },
+
+            append = &gtk::ScrolledWindow {
+                set_vexpand: true,
+                set_hexpand: true,
+
+                set_child = Some(&gtk::FlowBox) {
+                    set_valign: gtk::Align::Start,
+                    set_selection_mode: gtk::SelectionMode::None,
+                    set_homogeneous: true,
+                    set_row_spacing: 10,
+                    set_column_spacing: 10,
+                    set_margin_top: 10,
+                    set_margin_bottom: 10,
+                    set_margin_start: 10,
+                    set_margin_end: 10,
+                    factory!(model.items),
+                },
+            },
+
+            append = &gtk::Label {
+                set_margin_top: 5,
+                set_margin_bottom: 5,
+                set_label: watch!(&model.get_status_text()),
+            },
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use gtk::prelude::*;
+use relm4::*;
+
+use super::{SettingsPage, SettingsPageMsg};
+
+pub struct ListFilePicker {
+    visible: bool,
+}
+
+pub enum ListFilePickerMsg {
+    Show,
+    Close,
+    SelectFile(String),
+}
+
+impl ComponentUpdate<SettingsPage> for ListFilePicker {
+    fn init_model(_parent_model: &SettingsPage) -> Self {
+        Self { 
+            visible: false,
+        }
+    }
+
+    fn update(&mut self, msg: ListFilePickerMsg, _components: &(), _sender: Sender<ListFilePickerMsg>, parent_sender: Sender<SettingsPageMsg>) {
+        match msg {
+            ListFilePickerMsg::Show => {
+                self.visible = true;
+            },
+            ListFilePickerMsg::Close => {
+                self.visible = false;
+            },
+            ListFilePickerMsg::SelectFile(file) => {
+                self.visible = false;
+                send!(parent_sender, SettingsPageMsg::ListFileChanged(file));
+            }
+        }
+    }
+}
+
+impl Model for ListFilePicker {
+    type Msg = ListFilePickerMsg;
+    type Widgets = ListFilePickerWidgets;
+    type Components = ();
+}
+
+#[relm4::widget(pub)]
+impl Widgets<ListFilePicker, SettingsPage> for ListFilePickerWidgets {
+    view! {
+        gtk::FileChooserNative {
+            set_modal: true,
+            set_action: gtk::FileChooserAction::Open,
+            set_visible: watch!(model.visible),
+
+            connect_response(sender) => move |dialog, response| {
+                if response == gtk::ResponseType::Accept {
+                    if let Some(file) = dialog.file() {
+                        if let Some(file) = file.path() {
+                            if let Some(file) = file.to_str() {
+                                send!(sender, ListFilePickerMsg::SelectFile(file.into()));
+                            }
+                        }
+                    }
+                }
+
+                send!(sender, ListFilePickerMsg::Close);
+            }
+        }
+    }
+}
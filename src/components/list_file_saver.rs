@@ -0,0 +1,69 @@
+use gtk::prelude::*;
+use relm4::*;
+
+use super::{CompletePage, CompletePageMsg};
+
+pub struct ListFileSaver {
+    visible: bool,
+}
+
+pub enum ListFileSaverMsg {
+    Show,
+    Close,
+    SelectFile(String),
+}
+
+impl ComponentUpdate<CompletePage> for ListFileSaver {
+    fn init_model(_parent_model: &CompletePage) -> Self {
+        Self { 
+            visible: false,
+        }
+    }
+
+    fn update(&mut self, msg: ListFileSaverMsg, _components: &(), _sender: Sender<ListFileSaverMsg>, parent_sender: Sender<CompletePageMsg>) {
+        match msg {
+            ListFileSaverMsg::Show => {
+                self.visible = true;
+            },
+            ListFileSaverMsg::Close => {
+                self.visible = false;
+            },
+            ListFileSaverMsg::SelectFile(file) => {
+                self.visible = false;
+                send!(parent_sender, CompletePageMsg::SaveList(file));
+            }
+        }
+    }
+}
+
+impl Model for ListFileSaver {
+    type Msg = ListFileSaverMsg;
+    type Widgets = ListFileSaverWidgets;
+    type Components = ();
+}
+
+#[relm4::widget(pub)]
+impl Widgets<ListFileSaver, CompletePage> for ListFileSaverWidgets {
+    view! {
+        gtk::FileChooserNative {
+            set_modal: true,
+            set_action: gtk::FileChooserAction::Save,
+            set_current_name: "session.m3u",
+            set_visible: watch!(model.visible),
+
+            connect_response(sender) => move |dialog, response| {
+                if response == gtk::ResponseType::Accept {
+                    if let Some(file) = dialog.file() {
+                        if let Some(file) = file.path() {
+                            if let Some(file) = file.to_str() {
+                                send!(sender, ListFileSaverMsg::SelectFile(file.into()));
+                            }
+                        }
+                    }
+                }
+
+                send!(sender, ListFileSaverMsg::Close);
+            }
+        }
+    }
+}
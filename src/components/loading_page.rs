@@ -0,0 +1,85 @@
+use gtk::prelude::*;
+use relm4::*;
+
+use crate::app::{App, AppMsg};
+
+pub struct LoadingPage {
+    done: usize,
+    total: usize,
+}
+
+impl LoadingPage {
+    fn get_progress_text(&self) -> String {
+        match self.total {
+            0 => "Preparing session".into(),
+            total => format!("Downloading images ({} of {})", self.done, total),
+        }
+    }
+
+    fn get_fraction(&self) -> f64 {
+        match self.total {
+            0 => 0.0,
+            total => self.done as f64 / total as f64,
+        }
+    }
+}
+
+pub enum LoadingPageMsg {
+    Start,
+    Progress(usize, usize),
+}
+
+impl Model for LoadingPage {
+    type Msg = LoadingPageMsg;
+    type Widgets = LoadingPageWidgets;
+    type Components = ();
+}
+
+impl ComponentUpdate<App> for LoadingPage {
+    fn init_model(_parent_model: &App) -> Self {
+        Self {
+            done: 0,
+            total: 0,
+        }
+    }
+
+    fn update(&mut self, msg: LoadingPageMsg, _components: &(), _sender: Sender<LoadingPageMsg>, _parent_sender: Sender<AppMsg>) {
+        match msg {
+            LoadingPageMsg::Start => {
+                self.done = 0;
+                self.total = 0;
+            },
+            LoadingPageMsg::Progress(done, total) => {
+                self.done = done;
+                self.total = total;
+            },
+        }
+    }
+}
+
+#[relm4::widget(pub)]
+impl Widgets<LoadingPage, App> for LoadingPageWidgets {
+    view! {
+        gtk::Box {
+            set_orientation: gtk::Orientation::Vertical,
+            set_halign: gtk::Align::Center,
+            set_valign: gtk::Align::Center,
+            set_spacing: 20,
+
+            append = &gtk::Spinner {
+                set_spinning: true,
+                set_visible: watch!(model.total == 0),
+            },
+
+            append = &gtk::ProgressBar {
+                set_width_request: 300,
+                set_visible: watch!(model.total > 0),
+                set_fraction: watch!(model.get_fraction()),
+            },
+
+            append = &gtk::Label {
+                set_label: watch!(&model.get_progress_text()),
+            },
+        }
+    }
+}
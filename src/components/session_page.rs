@@ -1,32 +1,61 @@
 use gtk::prelude::*;
-use gtk::gdk_pixbuf::{Pixbuf, Colorspace, PixbufRotation};
+use gtk::gdk_pixbuf::{Pixbuf, Colorspace, InterpType, PixbufRotation};
+use gtk::gio;
 use relm4::*;
 
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+use std::path::Path;
+
 use crate::{
     app::{App, AppMsg},
+    archive::is_archive,
+    drop::{drop_target, dropped_paths},
+    idle::watch_idle_hint,
+    compare::{flip_drawing, overlay_drawing},
+    prompts::load_intermission_prompts,
+    source::expand_dropped_paths,
     session::Session,
+    settings::TimerDisplay,
+    index::ImageIndex,
+    tags::{is_sidecar, TagStore},
+    image_loader::load_pixbuf,
+    image_list::{ImageList, BLOCKLIST_FILE, FAVOURITES_FILE},
     components::*,
 };
 
+const PREVIEW_SIZE: i32 = 160;
+
+#[derive(Clone, Copy, PartialEq)]
+enum CompareMode {
+    SideBySide,
+    Overlay,
+}
+
 enum Timer {
     None,
     Session(usize),
+    Memory(usize),
+    Reveal(usize),
     Intermission(usize),
 }
 
 pub struct SessionPageComponents {
     timer: RelmComponent<SessionTimer, SessionPage>,
+    drawing_picker: RelmComponent<DrawingPicker, SessionPage>,
 }
 
 impl Components<SessionPage> for SessionPageComponents {
     fn init_components(parent_model: &SessionPage, parent_sender: Sender<SessionPageMsg>) -> Self {
         Self {
             timer: RelmComponent::new(parent_model, parent_sender.clone()),
+            drawing_picker: RelmComponent::new(parent_model, parent_sender.clone()),
         }
     }
 
     fn connect_parent(&mut self, parent_widgets: &<SessionPage as Model>::Widgets) {
         self.timer.connect_parent(parent_widgets);
+        self.drawing_picker.connect_parent(parent_widgets);
     }
 }
 
@@ -35,18 +64,242 @@ pub struct SessionPage {
     current_image: Pixbuf,
     timer: Timer,
     stopped: bool,
+    tags: Vec<String>,
+    favourite: bool,
+    monitor: Option<gio::FileMonitor>,
+    elapsed: usize,
+    extended: usize,
+    idle_proxy: Option<gio::DBusProxy>,
+    prompts: Vec<String>,
+    prompt: String,
+    preview: Option<Pixbuf>,
+    drawing: Option<Pixbuf>,
+    comparison: Option<Pixbuf>,
+    comparing: bool,
+    compare_mode: CompareMode,
+    drawing_opacity: f64,
+    drawing_flipped: bool,
+    drawing_offset_x: i32,
+    drawing_offset_y: i32,
+    drawing_scale: f64,
+    importing: Option<String>,
 }
 
 impl SessionPage {
+    fn watch_folder(&mut self, sender: Sender<SessionPageMsg>) {
+        if let Some(monitor) = self.monitor.take() {
+            monitor.cancel();
+        }
+
+        let folder = match &self.session.watch_folder {
+            Some(value) => value.clone(),
+            None => return,
+        };
+
+        let monitor = match gio::File::for_path(&folder).monitor_directory(gio::FileMonitorFlags::WATCH_MOVES, None::<&gio::Cancellable>) {
+            Ok(value) => value,
+            Err(_) => return,
+        };
+
+        monitor.connect_changed(move |_, file, other_file, event| {
+            let path = match folder_path(&folder, file) {
+                Some(value) => value,
+                None => return,
+            };
+
+            match event {
+                gio::FileMonitorEvent::ChangesDoneHint | gio::FileMonitorEvent::MovedIn => {
+                    send!(sender, SessionPageMsg::ImageAdded(path));
+                },
+                gio::FileMonitorEvent::Deleted | gio::FileMonitorEvent::MovedOut => {
+                    send!(sender, SessionPageMsg::ImageRemoved(path));
+                },
+                gio::FileMonitorEvent::Renamed => {
+                    send!(sender, SessionPageMsg::ImageRemoved(path));
+
+                    if let Some(path) = other_file.and_then(|value| folder_path(&folder, value)) {
+                        send!(sender, SessionPageMsg::ImageAdded(path));
+                    }
+                },
+                _ => {},
+            }
+        });
+
+        self.monitor = Some(monitor);
+    }
+
+    fn load_image(&mut self, image: String, parent_sender: &Sender<AppMsg>) {
+        match load_pixbuf(&image) {
+            Ok(value) => self.current_image = value,
+            Err(error) => send!(parent_sender, AppMsg::ShowError(error)),
+        };
+
+        self.tags = TagStore::load().image_tags(&image);
+        self.favourite = ImageList::load(FAVOURITES_FILE).contains(&image);
+
+        self.drawing = self.session.current_drawing().and_then(|drawing| load_pixbuf(&drawing).ok());
+        self.comparing = false;
+        self.update_comparison();
+    }
+
+    fn update_comparison(&mut self) {
+        let drawing = match &self.drawing {
+            Some(value) => flip_drawing(value, self.drawing_flipped),
+            None => {
+                self.comparison = None;
+                return;
+            },
+        };
+
+        self.comparison = match self.compare_mode {
+            CompareMode::SideBySide => Some(drawing),
+            CompareMode::Overlay => overlay_drawing(
+                &self.current_image,
+                &drawing,
+                self.drawing_opacity,
+                self.drawing_offset_x,
+                self.drawing_offset_y,
+                self.drawing_scale,
+            ),
+        };
+    }
+
+    fn side_by_side(&self) -> bool {
+        self.comparing && self.compare_mode == CompareMode::SideBySide
+    }
+
+    fn overlaid(&self) -> bool {
+        self.comparing && self.compare_mode == CompareMode::Overlay
+    }
+
+    fn get_displayed_image(&self) -> Option<&Pixbuf> {
+        match self.overlaid() {
+            true => self.comparison.as_ref().or(Some(&self.current_image)),
+            false => Some(&self.current_image),
+        }
+    }
+
+    fn start_intermission(&mut self) {
+        self.timer = Timer::Intermission(self.session.pause_time);
+
+        self.prompt = match self.session.settings.intermission_prompt {
+            true => self.prompts.choose(&mut thread_rng()).cloned().unwrap_or_default(),
+            false => "".into(),
+        };
+
+        self.preview = match self.session.settings.intermission_preview {
+            true => self.session.upcoming_image().and_then(|image| preview_pixbuf(&image)),
+            false => None,
+        };
+    }
+
+    fn end_image(&mut self, sender: &Sender<SessionPageMsg>) {
+        if self.session.pause_time > 0 {
+            self.start_intermission();
+        }
+        else {
+            self.timer = Timer::Session(self.session.image_time());
+            send!(sender, SessionPageMsg::NextImage);
+        }
+    }
+
+    fn get_prompt_text(&self) -> String {
+        self.session.current_prompt().unwrap_or_default()
+    }
+
+    fn get_favourite_icon(&self) -> String {
+        match self.favourite {
+            true => "starred-symbolic".into(),
+            false => "non-starred-symbolic".into(),
+        }
+    }
+
+    fn get_tags_text(&self) -> String {
+        if self.tags.is_empty() {
+            return "No tags".into();
+        }
+
+        self.tags.join(", ")
+    }
+
     fn get_current_image_position(&self) -> String {
+        if self.session.endless {
+            return format!("{}", self.session.current_image + 1);
+        }
+
         format!("{} / {}", self.session.current_image + 1, self.session.images.len())
     }
 
+    fn get_seed_text(&self) -> String {
+        format!("Session code: {}", self.session.seed)
+    }
+
+    fn watch_idle(&mut self, sender: Sender<SessionPageMsg>) {
+        if !self.session.settings.pause_when_idle {
+            self.idle_proxy = None;
+            return;
+        }
+
+        if self.idle_proxy.is_none() {
+            let ready_sender = sender.clone();
+
+            watch_idle_hint(
+                move |proxy| {
+                    send!(ready_sender, SessionPageMsg::IdleWatched(proxy));
+                },
+                move |idle| {
+                    if idle {
+                        send!(sender, SessionPageMsg::AutoPause);
+                    }
+                },
+            );
+        }
+    }
+
+    fn reset_timer(&mut self) {
+        self.elapsed = 0;
+        self.extended = 0;
+
+        let timed = self.session.image_time() > 0
+            || !self.session.settings.auto_advance
+            || self.session.settings.timer_display == TimerDisplay::Elapsed;
+
+        self.timer = match timed {
+            true => Timer::Session(self.session.image_time()),
+            false => Timer::None,
+        };
+    }
+
+    fn auto_advance(&self) -> bool {
+        self.session.settings.auto_advance && self.session.image_time() > 0
+    }
+
+    fn allowed_time(&self) -> usize {
+        self.session.image_time() + self.extended
+    }
+
+    fn overtime(&self) -> bool {
+        self.session.image_time() > 0 && self.elapsed > self.allowed_time()
+    }
+
     fn get_timer_countdown(&self) -> String {
         match self.timer {
             Timer::None => "".into(),
             Timer::Intermission(value) => value.to_string(),
-            Timer::Session(value) => value.to_string(),
+            Timer::Memory(value) => value.to_string(),
+            Timer::Reveal(value) => value.to_string(),
+            Timer::Session(value) => match self.session.settings.timer_display {
+                TimerDisplay::Elapsed => self.elapsed.to_string(),
+                TimerDisplay::Countdown if self.overtime() => format!("+{}", self.elapsed - self.allowed_time()),
+                TimerDisplay::Countdown => value.to_string(),
+            },
+        }
+    }
+
+    fn get_timer_classes(&self) -> Vec<&'static str> {
+        match self.overtime() {
+            true => vec!["error"],
+            false => vec![],
         }
     }
 
@@ -57,6 +310,13 @@ impl SessionPage {
         }
     }
 
+    fn memory(&self) -> bool {
+        match self.timer {
+            Timer::Memory(_) => true,
+            _ => false,
+        }
+    }
+
     fn intermission(&self) -> bool {
         match self.timer {
             Timer::Intermission(_) => true,
@@ -65,14 +325,56 @@ impl SessionPage {
     }
 }
 
+fn preview_pixbuf(image: &str) -> Option<Pixbuf> {
+    let pixbuf = load_pixbuf(image).ok()?;
+    let scale = PREVIEW_SIZE as f64 / i32::max(pixbuf.width(), pixbuf.height()) as f64;
+
+    pixbuf.scale_simple(
+        (pixbuf.width() as f64 * scale).round().max(1.0) as i32,
+        (pixbuf.height() as f64 * scale).round().max(1.0) as i32,
+        InterpType::Bilinear,
+    )
+}
+
+fn folder_path(folder: &str, file: &gio::File) -> Option<String> {
+    let name = file.basename()?;
+    let name = name.to_str()?;
+
+    Some(format!("{}/{}", folder, name))
+}
+
 pub enum SessionPageMsg {
     NewSession(Session),
     NextImage,
     PauseImage,
+    Finish,
+    AutoPause,
+    IdleWatched(gio::DBusProxy),
+    Reveal,
+    SkipIntermission,
+    ImportDrawing,
+    DrawingSelected(String),
+    ToggleCompare,
+    CompareOverlay(bool),
+    DrawingOpacityChanged(f64),
+    FlipDrawing(bool),
+    DrawingOffsetXChanged(i32),
+    DrawingOffsetYChanged(i32),
+    DrawingScaleChanged(f64),
+    WindowActiveChanged(bool),
+    DismissWarning,
+    ExtendTimer(usize),
+    RestartTimer,
     PrevImage,
     Flip,
     RotateLeft,
     RotateRight,
+    AddTag(String),
+    ToggleFavourite,
+    BlockImage,
+    ImageAdded(String),
+    ImageRemoved(String),
+    FilesDropped(Vec<String>),
     UpdateTimer,
 }
 
@@ -82,6 +384,12 @@ impl Model for SessionPage {
     type Components = SessionPageComponents;
 }
 
+impl DrawingPickerParent for SessionPage {
+    fn drawing_selected(file: String) -> SessionPageMsg {
+        SessionPageMsg::DrawingSelected(file)
+    }
+}
+
 impl ComponentUpdate<App> for SessionPage {
     fn init_model(_parent_model: &App) -> Self {
         Self {
@@ -89,56 +397,172 @@ impl ComponentUpdate<App> for SessionPage {
             current_image: Pixbuf::new(Colorspace::Rgb, true, 8, 1, 1).unwrap(),
             timer: Timer::None,
             stopped: false,
+            tags: vec![],
+            favourite: false,
+            monitor: None,
+            elapsed: 0,
+            extended: 0,
+            idle_proxy: None,
+            prompts: vec![],
+            prompt: "".into(),
+            preview: None,
+            drawing: None,
+            comparison: None,
+            comparing: false,
+            compare_mode: CompareMode::SideBySide,
+            drawing_opacity: 0.5,
+            drawing_flipped: false,
+            drawing_offset_x: 0,
+            drawing_offset_y: 0,
+            drawing_scale: 1.0,
+            importing: None,
         }
     }
 
-    fn update(&mut self, msg: SessionPageMsg, _components: &SessionPageComponents, sender: Sender<SessionPageMsg>, parent_sender: Sender<AppMsg>) {
+    fn update(&mut self, msg: SessionPageMsg, components: &SessionPageComponents, sender: Sender<SessionPageMsg>, parent_sender: Sender<AppMsg>) {
         match msg {
             SessionPageMsg::NewSession(session) => {
                 self.session = session;
+                self.stopped = false;
+                self.prompts = load_intermission_prompts();
+                self.watch_folder(sender.clone());
+                self.watch_idle(sender);
 
-                if let Some(image) = self.session.current_image() {
-                    match Pixbuf::from_file(image) {
-                        Ok(value) => self.current_image = value,
-                        Err(_) => send!(parent_sender, AppMsg::ShowError("Failed to load image".into())),
-                    };
+                if let Some(image) = self.session.current_existing_image() {
+                    self.load_image(image, &parent_sender);
                 }
                 else {
                     send!(parent_sender, AppMsg::ShowError("No images found".into()));
                 }
 
-                if self.session.session_time > 0 {
-                    self.timer = Timer::Session(self.session.session_time);
-                }
+                self.reset_timer();
             },
             SessionPageMsg::NextImage => {
-                if let Some(image) = self.session.next_image() {
-                    match Pixbuf::from_file(image) {
-                        Ok(value) => self.current_image = value,
-                        Err(_) => send!(parent_sender, AppMsg::ShowError("Failed to load image".into())),
-                    };
-
-                    if self.session.session_time > 0 {
-                        self.timer = Timer::Session(self.session.session_time);
-                    }
+                self.session.save_progress();
+
+                if let Some(image) = self.session.next_existing_image() {
+                    self.load_image(image, &parent_sender);
+
+                    self.reset_timer();
                 }
                 else {
-                    send!(parent_sender, AppMsg::ShowSessionComplete);
+                    send!(parent_sender, AppMsg::ShowSessionComplete(self.session.clone()));
                 }
             },
+            SessionPageMsg::DismissWarning => {
+                self.session.warning = None;
+            },
             SessionPageMsg::PauseImage => {
                 self.stopped = !self.stopped;
             },
+            SessionPageMsg::Reveal => {
+                if self.memory() {
+                    self.timer = Timer::Reveal(self.session.reveal_time);
+                }
+            },
+            SessionPageMsg::ImportDrawing => {
+                self.importing = self.session.current_image();
+
+                if self.importing.is_some() {
+                    components.drawing_picker.send(DrawingPickerMsg::Show).unwrap();
+                }
+            },
+            SessionPageMsg::DrawingSelected(drawing) => {
+                let image = match self.importing.take() {
+                    Some(value) => value,
+                    None => return,
+                };
+
+                match load_pixbuf(&drawing) {
+                    Ok(value) => {
+                        self.session.attach_drawing(image.clone(), drawing);
+
+                        if self.session.current_image() == Some(image) {
+                            self.drawing = Some(value);
+                            self.comparing = true;
+                            self.update_comparison();
+                        }
+                    },
+                    Err(error) => send!(parent_sender, AppMsg::ShowError(error)),
+                }
+            },
+            SessionPageMsg::ToggleCompare => {
+                self.comparing = !self.comparing && self.drawing.is_some();
+            },
+            SessionPageMsg::CompareOverlay(value) => {
+                self.compare_mode = match value {
+                    true => CompareMode::Overlay,
+                    false => CompareMode::SideBySide,
+                };
+                self.update_comparison();
+            },
+            SessionPageMsg::DrawingOpacityChanged(value) => {
+                self.drawing_opacity = value;
+                self.update_comparison();
+            },
+            SessionPageMsg::FlipDrawing(value) => {
+                self.drawing_flipped = value;
+                self.update_comparison();
+            },
+            SessionPageMsg::DrawingOffsetXChanged(value) => {
+                self.drawing_offset_x = value;
+                self.update_comparison();
+            },
+            SessionPageMsg::DrawingOffsetYChanged(value) => {
+                self.drawing_offset_y = value;
+                self.update_comparison();
+            },
+            SessionPageMsg::DrawingScaleChanged(value) => {
+                self.drawing_scale = value;
+                self.update_comparison();
+            },
+            SessionPageMsg::SkipIntermission => {
+                if self.intermission() {
+                    self.timer = Timer::Session(self.session.image_time());
+                    send!(sender, SessionPageMsg::NextImage);
+                }
+            },
+            SessionPageMsg::IdleWatched(proxy) => {
+                if self.session.settings.pause_when_idle {
+                    self.idle_proxy = Some(proxy);
+                }
+            },
+            SessionPageMsg::AutoPause => {
+                if self.session.current_image().is_some() {
+                    self.stopped = true;
+                }
+            },
+            SessionPageMsg::WindowActiveChanged(active) => {
+                if !active && self.session.settings.pause_on_focus_loss {
+                    send!(sender, SessionPageMsg::AutoPause);
+                }
+            },
+            SessionPageMsg::ExtendTimer(seconds) => {
+                if let Timer::Session(time_left) = self.timer {
+                    self.timer = Timer::Session(time_left + seconds);
+                    self.extended += seconds;
+                    self.session.record_extension(seconds);
+                }
+            },
+            SessionPageMsg::RestartTimer => {
+                if let Timer::Session(_) = self.timer {
+                    let granted = self.elapsed.saturating_sub(self.extended);
+
+                    self.timer = Timer::Session(self.session.image_time());
+                    self.extended = self.elapsed;
+                    self.session.record_extension(granted);
+                }
+            },
+            SessionPageMsg::Finish => {
+                self.session.save_progress();
+                self.session.finish();
+                send!(parent_sender, AppMsg::ShowSessionComplete(self.session.clone()));
+            },
             SessionPageMsg::PrevImage => {
-                if let Some(image) = self.session.previous_image() {
-                    match Pixbuf::from_file(image) {
-                        Ok(value) => self.current_image = value,
-                        Err(_) => send!(parent_sender, AppMsg::ShowError("Failed to load image".into())),
-                    };
-
-                    if self.session.session_time > 0 {
-                        self.timer = Timer::Session(self.session.session_time);
-                    }
+                if let Some(image) = self.session.previous_existing_image() {
+                    self.load_image(image, &parent_sender);
+
+                    self.reset_timer();
                 }
                 else {
                     send!(parent_sender, AppMsg::ShowSettings);
@@ -148,16 +572,74 @@ impl ComponentUpdate<App> for SessionPage {
                 if let Some(image) = self.current_image.flip(true) {
                     self.current_image = image;
                 }
+
+                self.update_comparison();
             },
             SessionPageMsg::RotateLeft => {
                 if let Some(image) = self.current_image.rotate_simple(PixbufRotation::Counterclockwise) {
                     self.current_image = image;
                 }
+
+                self.update_comparison();
             },
             SessionPageMsg::RotateRight => {
                 if let Some(image) = self.current_image.rotate_simple(PixbufRotation::Clockwise) {
                     self.current_image = image;
                 }
+
+                self.update_comparison();
+            },
+            SessionPageMsg::AddTag(tag) => {
+                if let Some(image) = self.session.current_image() {
+                    let mut tags = TagStore::load();
+                    tags.add_tag(&image, &tag);
+                    tags.save();
+
+                    self.tags = tags.image_tags(&image);
+                }
+            },
+            SessionPageMsg::ToggleFavourite => {
+                if let Some(image) = self.session.current_image() {
+                    let mut favourites = ImageList::load(FAVOURITES_FILE);
+                    self.favourite = favourites.toggle(&image);
+                    favourites.save();
+                }
+            },
+            SessionPageMsg::BlockImage => {
+                if let Some(image) = self.session.current_image() {
+                    let mut blocklist = ImageList::load(BLOCKLIST_FILE);
+                    blocklist.add(&image);
+                    blocklist.save();
+
+                    send!(sender, SessionPageMsg::NextImage);
+                }
+            },
+            SessionPageMsg::ImageAdded(image) => {
+                let path = Path::new(&image);
+                if path.is_dir() || is_sidecar(path) || is_archive(&image) {
+                    return;
+                }
+
+                if let Some(folder) = &self.session.watch_folder {
+                    let mut index = ImageIndex::load(folder);
+                    index.insert_image(&image);
+                    index.save();
+                }
+
+                self.session.add_image(image);
+            },
+            SessionPageMsg::ImageRemoved(image) => {
+                if let Some(folder) = &self.session.watch_folder {
+                    let mut index = ImageIndex::load(folder);
+                    index.remove_image(&image);
+                    index.save();
+                }
+
+                self.session.remove_image(&image);
+            },
+            SessionPageMsg::FilesDropped(paths) => {
+                let images = expand_dropped_paths(&paths);
+                self.session.queue_images(images);
             },
             SessionPageMsg::UpdateTimer => {
                 if self.stopped {
@@ -167,7 +649,7 @@ impl ComponentUpdate<App> for SessionPage {
                 match self.timer {
                     Timer::Intermission(time_left) => {
                         if time_left < 2 {
-                            self.timer = Timer::Session(self.session.session_time);
+                            self.timer = Timer::Session(self.session.image_time());
                             send!(sender, SessionPageMsg::NextImage);
                         }
                         else {
@@ -175,20 +657,47 @@ impl ComponentUpdate<App> for SessionPage {
                         }
                     },
                     Timer::Session(time_left) => {
-                        if time_left < 2 {
-                            if self.session.pause_time > 0 {
-                                self.timer = Timer::Intermission(self.session.pause_time);
+                        self.elapsed += 1;
+                        self.session.record_time(1);
+
+                        if !self.auto_advance() {
+                            self.timer = Timer::Session(time_left.saturating_sub(1));
+                        }
+                        else if time_left < 2 {
+                            if self.session.memory_time > 0 {
+                                self.timer = Timer::Memory(self.session.memory_time);
                             }
                             else {
-                                self.timer = Timer::Session(self.session.session_time);
-                                send!(sender, SessionPageMsg::NextImage);
+                                self.end_image(&sender);
                             }
                         }
                         else {
                             self.timer = Timer::Session(time_left - 1);
                         }
                     },
-                    _ => {},
+                    Timer::Memory(time_left) => {
+                        self.session.record_time(1);
+
+                        if time_left < 2 {
+                            self.timer = Timer::Reveal(self.session.reveal_time);
+                        }
+                        else {
+                            self.timer = Timer::Memory(time_left - 1);
+                        }
+                    },
+                    Timer::Reveal(time_left) => {
+                        self.session.record_time(1);
+
+                        if time_left < 2 {
+                            self.end_image(&sender);
+                        }
+                        else {
+                            self.timer = Timer::Reveal(time_left - 1);
+                        }
+                    },
+                    Timer::None => {
+                        self.session.record_time(1);
+                    },
                 }
             },
         }
@@ -198,8 +707,23 @@ impl ComponentUpdate<App> for SessionPage {
 #[relm4::widget(pub)]
 impl Widgets<SessionPage, App> for SessionPageWidgets {
     view! {
-        gtk::Box {
+        session_box = gtk::Box {
             set_orientation: gtk::Orientation::Vertical,
+            set_focusable: true,
+
+            append = &gtk::InfoBar {
+                set_message_type: gtk::MessageType::Warning,
+                set_show_close_button: true,
+                set_revealed: watch!(model.session.warning.is_some()),
+                connect_response(sender) => move |_, _| {
+                    send!(sender, SessionPageMsg::DismissWarning);
+                },
+
+                add_child = &gtk::Label {
+                    set_wrap: true,
+                    set_label: watch!(model.session.warning.as_deref().unwrap_or("")),
+                },
+            },
 
             append = &gtk::ActionBar {
                 set_visible: watch!(!model.intermission()),
@@ -209,6 +733,7 @@ impl Widgets<SessionPage, App> for SessionPageWidgets {
                 },
 
                 pack_end = &gtk::Label {
+                    set_css_classes: watch!(&model.get_timer_classes()),
                     set_label: watch!(&model.get_timer_countdown()),
                 },
 
@@ -240,6 +765,81 @@ impl Widgets<SessionPage, App> for SessionPageWidgets {
                         },
                     },
 
+                    append = &gtk::Button {
+                        set_visible: watch!(model.session.endless),
+                        set_icon_name: "media-playback-stop-symbolic",
+                        set_has_frame: false,
+                        set_tooltip_text: Some("Finish session"),
+
+                        connect_clicked(sender) => move |_| {
+                            send!(sender, SessionPageMsg::Finish);
+                        },
+                    },
+
+                    append = &gtk::Button {
+                        set_icon_name: watch!(&model.get_favourite_icon()),
+                        set_has_frame: false,
+                        set_tooltip_text: Some("Favourite"),
+                        
+                        connect_clicked(sender) => move |_| {
+                            send!(sender, SessionPageMsg::ToggleFavourite);
+                        },
+                    },
+
+                    append = &gtk::Button {
+                        set_icon_name: "action-unavailable-symbolic",
+                        set_has_frame: false,
+                        set_tooltip_text: Some("Never show this image again"),
+                        
+                        connect_clicked(sender) => move |_| {
+                            send!(sender, SessionPageMsg::BlockImage);
+                        },
+                    },
+
+                    append = &gtk::MenuButton {
+                        set_icon_name: "alarm-symbolic",
+                        set_has_frame: false,
+                        set_tooltip_text: Some("Add time"),
+
+                        set_popover = Some(&gtk::Popover) {
+                            set_child = Some(&gtk::Box) {
+                                set_orientation: gtk::Orientation::Vertical,
+
+                                append = &gtk::Button {
+                                    set_label: "+15 seconds (1)",
+                                    set_has_frame: false,
+                                    connect_clicked(sender) => move |_| {
+                                        send!(sender, SessionPageMsg::ExtendTimer(15));
+                                    },
+                                },
+
+                                append = &gtk::Button {
+                                    set_label: "+30 seconds (2)",
+                                    set_has_frame: false,
+                                    connect_clicked(sender) => move |_| {
+                                        send!(sender, SessionPageMsg::ExtendTimer(30));
+                                    },
+                                },
+
+                                append = &gtk::Button {
+                                    set_label: "+1 minute (3)",
+                                    set_has_frame: false,
+                                    connect_clicked(sender) => move |_| {
+                                        send!(sender, SessionPageMsg::ExtendTimer(60));
+                                    },
+                                },
+
+                                append = &gtk::Button {
+                                    set_label: "Restart timer (R)",
+                                    set_has_frame: false,
+                                    connect_clicked(sender) => move |_| {
+                                        send!(sender, SessionPageMsg::RestartTimer);
+                                    },
+                                },
+                            }
+                        }
+                    },
+
                     append = &gtk::MenuButton {
                         set_icon_name: "view-more-horizontal-symbolic",
                         set_has_frame: false,
@@ -248,6 +848,28 @@ impl Widgets<SessionPage, App> for SessionPageWidgets {
                             set_child = Some(&gtk::Box) {
                                 set_orientation: gtk::Orientation::Vertical,
 
+                                append = &gtk::Label {
+                                    set_selectable: true,
+                                    set_label: watch!(&model.get_seed_text()),
+                                },
+
+                                append = &gtk::Button {
+                                    set_label: "Import drawing",
+                                    set_has_frame: false,
+                                    connect_clicked(sender) => move |_| {
+                                        send!(sender, SessionPageMsg::ImportDrawing);
+                                    },
+                                },
+
+                                append = &gtk::Button {
+                                    set_label: "Compare with drawing",
+                                    set_has_frame: false,
+                                    set_sensitive: watch!(model.drawing.is_some()),
+                                    connect_clicked(sender) => move |_| {
+                                        send!(sender, SessionPageMsg::ToggleCompare);
+                                    },
+                                },
+
                                 append = &gtk::Button {
                                     set_label: "Flip",
                                     set_has_frame: false,
@@ -271,18 +893,130 @@ impl Widgets<SessionPage, App> for SessionPageWidgets {
                                         send!(sender, SessionPageMsg::RotateRight);
                                     },
                                 },
+
+                                append = &gtk::Label {
+                                    set_wrap: true,
+                                    set_max_width_chars: 20,
+                                    set_label: watch!(&model.get_tags_text()),
+                                },
+
+                                append = &gtk::Entry {
+                                    set_placeholder_text: Some("Add tag"),
+                                    connect_activate(sender) => move |entry| {
+                                        send!(sender, SessionPageMsg::AddTag(entry.text().to_string()));
+                                        entry.set_text("");
+                                    },
+                                },
                             }
                         }
                     },
                 },
             },
 
-            append = &gtk::Picture {
-                set_visible: watch!(!model.intermission()),
+            append = &gtk::ActionBar {
+                set_visible: watch!(model.comparing && !model.intermission()),
+
+                pack_start = &gtk::ToggleButton {
+                    set_label: "Overlay",
+                    set_active: watch!(model.compare_mode == CompareMode::Overlay),
+                    connect_toggled(sender) => move |button| {
+                        send!(sender, SessionPageMsg::CompareOverlay(button.is_active()));
+                    },
+                },
+
+                pack_start = &gtk::CheckButton {
+                    set_label: Some("Flip drawing"),
+                    connect_toggled(sender) => move |check_button| {
+                        send!(sender, SessionPageMsg::FlipDrawing(check_button.is_active()));
+                    },
+                },
+
+                pack_end = &gtk::Box {
+                    set_visible: watch!(model.overlaid()),
+                    set_spacing: 5,
+
+                    append = &gtk::Label {
+                        set_label: "Opacity",
+                    },
+                    append = &gtk::Scale {
+                        set_width_request: 100,
+                        set_adjustment: &gtk::Adjustment::new(0.5, 0.0, 1.0, 0.05, 0.1, 0.0),
+                        connect_value_changed(sender) => move |scale| {
+                            send!(sender, SessionPageMsg::DrawingOpacityChanged(scale.value()));
+                        },
+                    },
+
+                    append = &gtk::Label {
+                        set_label: "X",
+                    },
+                    append = &gtk::SpinButton {
+                        set_adjustment: &gtk::Adjustment::new(0.0, -5000.0, 5000.0, 5.0, 50.0, 0.0),
+                        connect_value_changed(sender) => move |spin_button| {
+                            send!(sender, SessionPageMsg::DrawingOffsetXChanged(spin_button.value() as i32));
+                        },
+                    },
+
+                    append = &gtk::Label {
+                        set_label: "Y",
+                    },
+                    append = &gtk::SpinButton {
+                        set_adjustment: &gtk::Adjustment::new(0.0, -5000.0, 5000.0, 5.0, 50.0, 0.0),
+                        connect_value_changed(sender) => move |spin_button| {
+                            send!(sender, SessionPageMsg::DrawingOffsetYChanged(spin_button.value() as i32));
+                        },
+                    },
+
+                    append = &gtk::Label {
+                        set_label: "Scale",
+                    },
+                    append = &gtk::SpinButton {
+                        set_digits: 2,
+                        set_adjustment: &gtk::Adjustment::new(1.0, 0.1, 5.0, 0.05, 0.25, 0.0),
+                        connect_value_changed(sender) => move |spin_button| {
+                            send!(sender, SessionPageMsg::DrawingScaleChanged(spin_button.value()));
+                        },
+                    },
+                },
+            },
+
+            append = &gtk::Label {
+                set_visible: watch!(!model.intermission() && !model.session.deck.is_empty()),
+                set_margin_top: 5,
+                set_margin_bottom: 5,
+                set_wrap: true,
+                set_label: watch!(&model.get_prompt_text()),
+            },
+
+            append = &gtk::Overlay {
+                set_visible: watch!(!model.intermission() && !model.memory()),
                 set_vexpand: true,
                 set_hexpand: true,
 
-                set_pixbuf: watch!(Some(&model.current_image)),
+                set_child = Some(&gtk::Box) {
+                    set_spacing: 5,
+                    set_homogeneous: true,
+
+                    append = &gtk::Picture {
+                        set_pixbuf: watch!(model.get_displayed_image()),
+                    },
+
+                    append = &gtk::Picture {
+                        set_visible: watch!(model.side_by_side()),
+                        set_pixbuf: watch!(model.comparison.as_ref()),
+                    },
+                },
+
+                add_overlay = &gtk::Label {
+                    set_visible: watch!(model.stopped),
+                    set_halign: gtk::Align::Center,
+                    set_valign: gtk::Align::Center,
+                    add_css_class: "osd",
+                    set_margin_top: 10,
+                    set_margin_bottom: 10,
+                    set_margin_start: 10,
+                    set_margin_end: 10,
+                    set_label: "Paused — press Space to resume",
+                },
             },
 
             append = &gtk::Box {
@@ -300,7 +1034,116 @@ impl Widgets<SessionPage, App> for SessionPageWidgets {
                 append = &gtk::Label {
                     set_label: "Intermission",
                 },
+
+                append = &gtk::Label {
+                    set_visible: watch!(!model.prompt.is_empty()),
+                    set_wrap: true,
+                    set_max_width_chars: 30,
+                    set_justify: gtk::Justification::Center,
+                    set_label: watch!(&model.prompt),
+                },
+
+                append = &gtk::Box {
+                    set_visible: watch!(model.preview.is_some()),
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_spacing: 5,
+
+                    append = &gtk::Label {
+                        add_css_class: "dim-label",
+                        set_label: "Next image",
+                    },
+
+                    append = &gtk::Picture {
+                        set_can_shrink: false,
+                        set_pixbuf: watch!(model.preview.as_ref()),
+                    },
+                },
+
+                append = &gtk::Button {
+                    set_visible: watch!(model.session.settings.intermission_skip),
+                    set_label: "Skip intermission",
+                    connect_clicked(sender) => move |_| {
+                        send!(sender, SessionPageMsg::SkipIntermission);
+                    },
+                },
             },
+
+            append = &gtk::Box {
+                set_visible: watch!(model.memory()),
+                set_orientation: gtk::Orientation::Vertical,
+                set_vexpand: true,
+                set_halign: gtk::Align::Center,
+                set_valign: gtk::Align::Center,
+                set_spacing: 50,
+
+                append = &gtk::Label {
+                    set_label: watch!(&model.get_timer_countdown()),
+                },
+
+                append = &gtk::Label {
+                    set_label: "Draw from memory",
+                },
+
+                append = &gtk::Button {
+                    set_label: "Reveal now",
+                    connect_clicked(sender) => move |_| {
+                        send!(sender, SessionPageMsg::Reveal);
+                    },
+                },
+            },
+        }
+    }
+
+    fn post_init() {
+        let target = drop_target();
+        let drop_sender = sender.clone();
+        target.connect_drop(move |_, value, _, _| {
+            let paths = dropped_paths(value);
+            if paths.is_empty() {
+                return false;
+            }
+
+            send!(drop_sender, SessionPageMsg::FilesDropped(paths));
+            true
+        });
+        session_box.add_controller(&target);
+
+        let shortcuts = gtk::ShortcutController::new();
+        shortcuts.set_scope(gtk::ShortcutScope::Local);
+
+        for (trigger, seconds) in [("1", 15), ("2", 30), ("3", 60)] {
+            let shortcut_sender = sender.clone();
+            let action = gtk::CallbackAction::new(move |_, _| {
+                send!(shortcut_sender, SessionPageMsg::ExtendTimer(seconds));
+                true
+            });
+
+            let trigger = gtk::ShortcutTrigger::parse_string(trigger);
+            shortcuts.add_shortcut(&gtk::Shortcut::new(trigger.as_ref(), Some(&action)));
         }
+
+        let shortcut_sender = sender.clone();
+        let action = gtk::CallbackAction::new(move |_, _| {
+            send!(shortcut_sender, SessionPageMsg::RestartTimer);
+            true
+        });
+
+        let trigger = gtk::ShortcutTrigger::parse_string("r");
+        shortcuts.add_shortcut(&gtk::Shortcut::new(trigger.as_ref(), Some(&action)));
+
+        let shortcut_sender = sender.clone();
+        let action = gtk::CallbackAction::new(move |_, _| {
+            send!(shortcut_sender, SessionPageMsg::PauseImage);
+            true
+        });
+
+        let trigger = gtk::ShortcutTrigger::parse_string("space");
+        shortcuts.add_shortcut(&gtk::Shortcut::new(trigger.as_ref(), Some(&action)));
+
+        session_box.add_controller(&shortcuts);
+
+        session_box.connect_map(|session_box| {
+            session_box.grab_focus();
+        });
     }
-}
\ No newline at end of file
+}
@@ -1,19 +1,245 @@
 use gtk::prelude::*;
+use gtk::glib;
+use relm4::factory::{FactoryPrototype, FactoryVec};
 use relm4::*;
 
-use std::time::Duration;
+use std::{fs::create_dir_all, path::Path, thread, time::Duration};
 use super::folder_picker::{FolderPicker, FolderPickerMsg};
+use super::list_file_picker::{ListFilePicker, ListFilePickerMsg};
 
 use crate::{
-    settings::Settings,
+    settings::{format_duration, OrientationFilter, SelectionMode, SessionSource, Settings, TimerDisplay},
+    session::Session,
     app::{App, AppMsg},
+    cli::parse_seed,
+    archive::is_archive,
+    index::ImageIndex,
+    planner::PoseCurve,
+    prompts::{format_prompt_card, load_intermission_prompts, load_prompt_deck, save_intermission_prompts, save_prompt_deck},
+    drop::{drop_target, dropped_paths},
+    source::{expand_dropped_paths, save_image_list, DROPPED_LIST_FILE},
+    storage::data_path,
+    recent::RecentFolders,
 };
 
+pub struct RecentFolderItem {
+    path: String,
+    name: String,
+    pinned: bool,
+    exists: bool,
+}
+
+impl RecentFolderItem {
+    fn get_name_text(&self) -> String {
+        match self.exists {
+            true => self.name.clone(),
+            false => format!("{} (missing)", self.name),
+        }
+    }
+
+    fn get_pin_icon(&self) -> String {
+        match self.pinned {
+            true => "starred-symbolic".into(),
+            false => "non-starred-symbolic".into(),
+        }
+    }
+}
+
+#[relm4::factory_prototype(pub)]
+impl FactoryPrototype for RecentFolderItem {
+    type Factory = FactoryVec<Self>;
+    type Widgets = RecentFolderItemWidgets;
+    type View = gtk::Box;
+    type Msg = SettingsPageMsg;
+
+    view! {
+        gtk::Box {
+            set_spacing: 5,
+
+            append = &gtk::Button {
+                set_hexpand: true,
+                set_has_frame: false,
+                set_sensitive: self.exists,
+                set_tooltip_text: Some(self.path.as_str()),
+
+                set_child = Some(&gtk::Label) {
+                    set_halign: gtk::Align::Start,
+                    set_ellipsize: gtk::pango::EllipsizeMode::Middle,
+                    set_max_width_chars: 30,
+                    set_label: watch!(&self.get_name_text()),
+                },
+
+                connect_clicked(sender, key) => move |button| {
+                    if let Some(popover) = button.ancestor(gtk::Popover::static_type()) {
+                        if let Ok(popover) = popover.downcast::<gtk::Popover>() {
+                            popover.popdown();
+                        }
+                    }
+
+                    send!(sender, SettingsPageMsg::SelectRecentFolder(key));
+                },
+            },
+
+            append = &gtk::Button {
+                set_icon_name: watch!(&self.get_pin_icon()),
+                set_has_frame: false,
+                set_tooltip_text: Some("Pin folder"),
+                connect_clicked(sender, key) => move |_| {
+                    send!(sender, SettingsPageMsg::PinRecentFolder(key));
+                },
+            },
+
+            append = &gtk::Button {
+                set_icon_name: "edit-delete-symbolic",
+                set_has_frame: false,
+                set_tooltip_text: Some("Remove from recent folders"),
+                connect_clicked(sender, key) => move |_| {
+                    send!(sender, SettingsPageMsg::RemoveRecentFolder(key));
+                },
+            },
+        }
+    }
+
+    fn position(&self, _index: &usize) {}
+}
+
+const POOL_COUNT_DELAY: Duration = Duration::from_millis(400);
+const PROMPTS_SAVE_DELAY: Duration = Duration::from_secs(1);
+
 pub struct SettingsPage {
     settings: Settings,
+    scan_progress: Option<f64>,
+    recent_folders: RecentFolders,
+    recent_items: FactoryVec<RecentFolderItem>,
+    pool_size: Option<usize>,
+    pool_generation: u64,
+    intermission_prompts: Option<String>,
+    prompt_deck: Option<String>,
+    prompts_generation: u64,
 }
 
 impl SettingsPage {
+    fn count_pool(&mut self, sender: Sender<SettingsPageMsg>) {
+        self.pool_generation += 1;
+        self.pool_size = None;
+
+        if self.settings.source == SessionSource::Server {
+            return;
+        }
+
+        let generation = self.pool_generation;
+        glib::timeout_add_local_once(POOL_COUNT_DELAY, move || {
+            send!(sender, SettingsPageMsg::CountPool(generation));
+        });
+    }
+
+    fn edit_prompts(&mut self, sender: Sender<SettingsPageMsg>) {
+        self.prompts_generation += 1;
+
+        let generation = self.prompts_generation;
+        glib::timeout_add_local_once(PROMPTS_SAVE_DELAY, move || {
+            send!(sender, SettingsPageMsg::SavePrompts(generation));
+        });
+    }
+
+    fn save_prompts(&mut self) {
+        if let Some(value) = self.intermission_prompts.take() {
+            save_intermission_prompts(&value);
+        }
+
+        if let Some(value) = self.prompt_deck.take() {
+            save_prompt_deck(&value);
+        }
+    }
+
+    pub fn get_pool_text(&self) -> String {
+        let pool_size = match self.pool_size {
+            Some(value) => value,
+            None if self.settings.source == SessionSource::Server => return "Image count is shown when the session starts".into(),
+            None => return "Counting images…".into(),
+        };
+
+        if self.settings.endless {
+            return format!("{} images available, endless", pool_size);
+        }
+
+        let images = usize::min(pool_size, self.settings.session_images());
+
+        match self.settings.estimated_time(images) {
+            Some(value) => format!("{} images available, about {}", pool_size, format_duration(value)),
+            None => format!("{} images available, untimed", pool_size),
+        }
+    }
+
+    pub fn get_pool_warning_text(&self) -> String {
+        format!("Only {} images match, the session will be shorter", self.pool_size.unwrap_or(0))
+    }
+
+    pub fn pool_too_small(&self) -> bool {
+        match self.pool_size {
+            Some(value) => !self.settings.endless && value < self.settings.session_images(),
+            None => false,
+        }
+    }
+
+    fn update_recent_items(&mut self) {
+        self.recent_items.clear();
+
+        for folder in self.recent_folders.folders() {
+            let name = match Path::new(&folder.path).file_name().and_then(|value| value.to_str()) {
+                Some(value) => value.to_owned(),
+                None => folder.path.clone(),
+            };
+
+            self.recent_items.push(RecentFolderItem {
+                path: folder.path.clone(),
+                exists: folder.exists(),
+                pinned: folder.pinned,
+                name,
+            });
+        }
+    }
+
+    fn recent_folder_path(&self, index: usize) -> Option<String> {
+        self.recent_items.get(index).map(|item| item.path.clone())
+    }
+
+    fn scan_folder(&mut self, rescan: bool, sender: Sender<SettingsPageMsg>) {
+        if self.settings.folder.is_empty() || is_archive(&self.settings.folder) {
+            return;
+        }
+
+        self.scan_progress = Some(0.0);
+
+        let folder = self.settings.folder.clone();
+        thread::spawn(move || {
+            let mut index = ImageIndex::load(&folder);
+
+            let progress = |done: usize, total: usize| {
+                send!(sender, SettingsPageMsg::ScanProgress(folder.clone(), done, total));
+            };
+
+            let result = match rescan {
+                true => index.rescan(progress),
+                false => index.refresh(progress),
+            };
+
+            if result.is_ok() {
+                index.save();
+            }
+
+            send!(sender, SettingsPageMsg::ScanFinished(folder));
+        });
+    }
+
+    pub fn scanning(&self) -> bool {
+        self.scan_progress.is_some()
+    }
+
+    pub fn get_scan_fraction(&self) -> f64 {
+        self.scan_progress.unwrap_or(0.0)
+    }
+
     pub fn get_folder_text(&self) -> String {
         if self.settings.folder.is_empty() {
             return "None".into();
@@ -21,30 +247,90 @@ impl SettingsPage {
 
         self.settings.folder.clone()
     }
+
+    pub fn get_list_file_text(&self) -> String {
+        if self.settings.list_file.is_empty() {
+            return "None".into();
+        }
+
+        self.settings.list_file.clone()
+    }
+
+    pub fn get_seed_text(&self) -> String {
+        match self.settings.seed {
+            Some(value) => value.to_string(),
+            None => "".into(),
+        }
+    }
 }
 
 pub enum SettingsPageMsg {
     Start,
+    Browse,
+    ShowGallery,
     SelectFolder,
+    SelectArchive,
+    SourceChanged(SessionSource),
     FolderChanged(String),
+    Rescan,
+    ScanProgress(String, usize, usize),
+    ScanFinished(String),
+    SelectListFile,
+    ListFileChanged(String),
+    ServerUrlChanged(String),
     ImagesChanged(usize),
+    EndlessChanged(bool),
+    TimeBudgetChanged(Option<u64>),
+    PoseCurveChanged(PoseCurve),
+    AutoAdvanceChanged(bool),
+    TimerDisplayChanged(TimerDisplay),
+    PauseOnFocusLossChanged(bool),
+    PauseWhenIdleChanged(bool),
+    MemoryTimeChanged(Option<u64>),
+    RevealTimeChanged(u64),
+    IntermissionPromptChanged(bool),
+    IntermissionPromptsEdited(String),
+    IntermissionPreviewChanged(bool),
+    IntermissionSkipChanged(bool),
+    ExercisePromptsChanged(bool),
+    PromptDeckEdited(String),
+    SelectionModeChanged(SelectionMode),
     DurationChanged(Option<u64>),
     PauseChanged(Option<u64>),
+    SeedChanged(Option<u64>),
+    TagQueryChanged(String),
+    OrientationChanged(OrientationFilter),
+    MinWidthChanged(u32),
+    MinHeightChanged(u32),
+    MinAspectRatioChanged(Option<f64>),
+    MaxAspectRatioChanged(Option<f64>),
+    SkipDuplicatesChanged(bool),
+    WatchFolderChanged(bool),
+    FilesDropped(Vec<String>),
+    SelectRecentFolder(usize),
+    PinRecentFolder(usize),
+    RemoveRecentFolder(usize),
+    CountPool(u64),
+    SavePrompts(u64),
+    PoolCounted(u64, usize),
 }
 
 pub struct SettingsPageComponents {
     folder_picker: RelmComponent<FolderPicker, SettingsPage>,
+    list_file_picker: RelmComponent<ListFilePicker, SettingsPage>,
 }
 
 impl Components<SettingsPage> for SettingsPageComponents {
     fn init_components(parent_model: &SettingsPage, parent_sender: Sender<SettingsPageMsg>) -> Self {
         Self {
             folder_picker: RelmComponent::new(parent_model, parent_sender.clone()),
+            list_file_picker: RelmComponent::new(parent_model, parent_sender.clone()),
         }
     }
 
     fn connect_parent(&mut self, parent_widgets: &<SettingsPage as Model>::Widgets) {
         self.folder_picker.connect_parent(parent_widgets);
+        self.list_file_picker.connect_parent(parent_widgets);
     }
 }
 
@@ -55,26 +341,180 @@ impl Model for SettingsPage {
 }
 
 impl ComponentUpdate<App> for SettingsPage {
-    fn init_model(_parent_model: &App) -> Self {
-        Self {
-            settings: Settings::new(),
-        }
+    fn init_model(parent_model: &App) -> Self {
+        let mut model = Self {
+            settings: parent_model.settings.clone(),
+            scan_progress: None,
+            recent_folders: RecentFolders::load(),
+            recent_items: FactoryVec::new(),
+            pool_size: None,
+            pool_generation: 0,
+            intermission_prompts: None,
+            prompt_deck: None,
+            prompts_generation: 0,
+        };
+
+        model.update_recent_items();
+        model
     }
 
-    fn update(&mut self, msg: SettingsPageMsg, components: &SettingsPageComponents, _sender: Sender<SettingsPageMsg>, parent_sender: Sender<AppMsg>) {
+    fn update(&mut self, msg: SettingsPageMsg, components: &SettingsPageComponents, sender: Sender<SettingsPageMsg>, parent_sender: Sender<AppMsg>) {
         match msg {
             SettingsPageMsg::Start => {
+                self.save_prompts();
                 send!(parent_sender, AppMsg::StartNewSession(self.settings.clone()));
             },
+            SettingsPageMsg::Browse => {
+                self.save_prompts();
+                send!(parent_sender, AppMsg::ShowLibrary(self.settings.clone()));
+            },
+            SettingsPageMsg::ShowGallery => {
+                self.save_prompts();
+                send!(parent_sender, AppMsg::ShowGallery);
+            },
             SettingsPageMsg::SelectFolder => {
                 components.folder_picker.send(FolderPickerMsg::Show).unwrap();
             },
+            SettingsPageMsg::SelectArchive => {
+                components.folder_picker.send(FolderPickerMsg::ShowArchive).unwrap();
+            },
+            SettingsPageMsg::SourceChanged(value) => {
+                self.settings.source = value;
+                self.count_pool(sender);
+            },
+            SettingsPageMsg::Rescan => {
+                self.scan_folder(true, sender);
+            },
+            SettingsPageMsg::ScanProgress(folder, done, total) => {
+                if folder == self.settings.folder && total > 0 {
+                    self.scan_progress = Some(done as f64 / total as f64);
+                }
+            },
+            SettingsPageMsg::ScanFinished(folder) => {
+                if folder == self.settings.folder {
+                    self.scan_progress = None;
+                    self.count_pool(sender);
+                }
+            },
             SettingsPageMsg::FolderChanged(value) => {
-                self.settings.folder = value;    
+                self.recent_folders.add(&value);
+                self.recent_folders.save();
+                self.update_recent_items();
+
+                self.settings.folder = value;
+                self.scan_folder(false, sender.clone());
+
+                if !self.scanning() {
+                    self.count_pool(sender);
+                }
+            },
+            SettingsPageMsg::SelectRecentFolder(index) => {
+                if let Some(path) = self.recent_folder_path(index) {
+                    send!(sender, SettingsPageMsg::FolderChanged(path));
+                }
+            },
+            SettingsPageMsg::PinRecentFolder(index) => {
+                if let Some(path) = self.recent_folder_path(index) {
+                    self.recent_folders.toggle_pin(&path);
+                    self.recent_folders.save();
+                    self.update_recent_items();
+                }
+            },
+            SettingsPageMsg::RemoveRecentFolder(index) => {
+                if let Some(path) = self.recent_folder_path(index) {
+                    self.recent_folders.remove(&path);
+                    self.recent_folders.save();
+                    self.update_recent_items();
+                }
+            },
+            SettingsPageMsg::CountPool(generation) => {
+                if generation != self.pool_generation {
+                    return;
+                }
+
+                let settings = self.settings.clone();
+                thread::spawn(move || {
+                    let count = match Session::count_pool(&settings) {
+                        Ok(value) => value,
+                        Err(_) => 0,
+                    };
+
+                    send!(sender, SettingsPageMsg::PoolCounted(generation, count));
+                });
+            },
+            SettingsPageMsg::PoolCounted(generation, count) => {
+                if generation == self.pool_generation {
+                    self.pool_size = Some(count);
+                }
+            },
+            SettingsPageMsg::SelectListFile => {
+                components.list_file_picker.send(ListFilePickerMsg::Show).unwrap();
+            },
+            SettingsPageMsg::ListFileChanged(value) => {
+                self.settings.list_file = value;
+                self.count_pool(sender);
+            },
+            SettingsPageMsg::ServerUrlChanged(value) => {
+                self.settings.server_url = value;
             },
             SettingsPageMsg::ImagesChanged(value) => {
                 self.settings.images_number = value;    
             },
+            SettingsPageMsg::EndlessChanged(value) => {
+                self.settings.endless = value;
+            },
+            SettingsPageMsg::TimeBudgetChanged(value) => {
+                self.settings.time_budget = value.map(Duration::from_secs);
+            },
+            SettingsPageMsg::PoseCurveChanged(value) => {
+                self.settings.pose_curve = value;
+            },
+            SettingsPageMsg::AutoAdvanceChanged(value) => {
+                self.settings.auto_advance = value;
+            },
+            SettingsPageMsg::TimerDisplayChanged(value) => {
+                self.settings.timer_display = value;
+            },
+            SettingsPageMsg::PauseOnFocusLossChanged(value) => {
+                self.settings.pause_on_focus_loss = value;
+            },
+            SettingsPageMsg::PauseWhenIdleChanged(value) => {
+                self.settings.pause_when_idle = value;
+            },
+            SettingsPageMsg::MemoryTimeChanged(value) => {
+                self.settings.memory_time = value.map(Duration::from_secs);
+            },
+            SettingsPageMsg::RevealTimeChanged(value) => {
+                self.settings.reveal_time = Duration::from_secs(value);
+            },
+            SettingsPageMsg::IntermissionPromptChanged(value) => {
+                self.settings.intermission_prompt = value;
+            },
+            SettingsPageMsg::IntermissionPromptsEdited(value) => {
+                self.intermission_prompts = Some(value);
+                self.edit_prompts(sender);
+            },
+            SettingsPageMsg::SavePrompts(generation) => {
+                if generation == self.prompts_generation {
+                    self.save_prompts();
+                }
+            },
+            SettingsPageMsg::IntermissionPreviewChanged(value) => {
+                self.settings.intermission_preview = value;
+            },
+            SettingsPageMsg::IntermissionSkipChanged(value) => {
+                self.settings.intermission_skip = value;
+            },
+            SettingsPageMsg::ExercisePromptsChanged(value) => {
+                self.settings.exercise_prompts = value;
+            },
+            SettingsPageMsg::PromptDeckEdited(value) => {
+                self.prompt_deck = Some(value);
+                self.edit_prompts(sender);
+            },
+            SettingsPageMsg::SelectionModeChanged(value) => {
+                self.settings.selection_mode = value;
+            },
             SettingsPageMsg::DurationChanged(value) => {
                 self.settings.duration = match value {
                     Some(value) => Some(Duration::from_secs(value)),
@@ -87,6 +527,73 @@ impl ComponentUpdate<App> for SettingsPage {
                     None => None,
                 };
             },
+            SettingsPageMsg::SeedChanged(value) => {
+                self.settings.seed = value;
+            },
+            SettingsPageMsg::TagQueryChanged(value) => {
+                self.settings.tag_query = value;
+                self.count_pool(sender);
+            },
+            SettingsPageMsg::OrientationChanged(value) => {
+                self.settings.orientation = value;
+                self.count_pool(sender);
+            },
+            SettingsPageMsg::MinWidthChanged(value) => {
+                self.settings.min_width = value;
+                self.count_pool(sender);
+            },
+            SettingsPageMsg::MinHeightChanged(value) => {
+                self.settings.min_height = value;
+                self.count_pool(sender);
+            },
+            SettingsPageMsg::MinAspectRatioChanged(value) => {
+                self.settings.min_aspect_ratio = value;
+                self.count_pool(sender);
+            },
+            SettingsPageMsg::MaxAspectRatioChanged(value) => {
+                self.settings.max_aspect_ratio = value;
+                self.count_pool(sender);
+            },
+            SettingsPageMsg::WatchFolderChanged(value) => {
+                self.settings.watch_folder = value;
+            },
+            SettingsPageMsg::SkipDuplicatesChanged(value) => {
+                self.settings.skip_duplicates = value;
+                self.count_pool(sender);
+            },
+            SettingsPageMsg::FilesDropped(paths) => {
+                if let [path] = paths.as_slice() {
+                    if Path::new(path).is_dir() || is_archive(path) {
+                        self.settings.source = SessionSource::Folder;
+                        send!(sender, SettingsPageMsg::FolderChanged(path.clone()));
+                        return;
+                    }
+                }
+
+                let images = expand_dropped_paths(&paths);
+                if images.is_empty() {
+                    return;
+                }
+
+                let list_file = data_path(DROPPED_LIST_FILE);
+                if let Some(folder) = list_file.parent() {
+                    let _ = create_dir_all(folder);
+                }
+
+                let list_file = match list_file.to_str() {
+                    Some(value) => value.to_owned(),
+                    None => return,
+                };
+
+                match save_image_list(&list_file, &images) {
+                    Ok(_) => {
+                        self.settings.source = SessionSource::List;
+                        self.settings.list_file = list_file;
+                        self.count_pool(sender);
+                    },
+                    Err(_) => send!(parent_sender, AppMsg::ShowError("Failed to save dropped images".into())),
+                }
+            },
         }
     }
 }
@@ -94,7 +601,7 @@ impl ComponentUpdate<App> for SettingsPage {
 #[relm4::widget(pub)]
 impl Widgets<SettingsPage, App> for SettingsPageWidgets {
     view! {
-        gtk::Box {
+        settings_box = gtk::Box {
             set_orientation: gtk::Orientation::Vertical,
             set_spacing: 50,
             set_halign: gtk::Align::Center,
@@ -109,6 +616,37 @@ impl Widgets<SettingsPage, App> for SettingsPageWidgets {
                 set_orientation: gtk::Orientation::Vertical,
                 set_spacing: 5,
 
+                append = &gtk::Box {
+                    set_spacing: 5,
+                    set_homogeneous: true,
+
+                    append = &gtk::Label {
+                        set_halign: gtk::Align::Start,
+                        set_label: "Source",
+                    },
+                    append = &gtk::Image {
+                        set_halign: gtk::Align::End,
+                        set_icon_name: Some("help-about-symbolic"),
+                        set_has_tooltip: true,
+                        set_tooltip_text: Some("Select where images are taken from"),
+                    },
+                },
+                append: source = &gtk::ComboBoxText {
+                    set_active_id: watch!(Some(model.settings.source.id())),
+                    connect_changed(sender) => move |combo_box| {
+                        if let Some(id) = combo_box.active_id() {
+                            if let Some(value) = SessionSource::from_id(&id) {
+                                send!(sender, SettingsPageMsg::SourceChanged(value));
+                            }
+                        }
+                    },
+                },
+            },
+            append = &gtk::Box {
+                set_visible: watch!(model.settings.source == SessionSource::Folder),
+                set_orientation: gtk::Orientation::Vertical,
+                set_spacing: 5,
+
                 append = &gtk::Box {
                     set_spacing: 5,
                     set_homogeneous: true,
@@ -121,21 +659,145 @@ impl Widgets<SettingsPage, App> for SettingsPageWidgets {
                         set_halign: gtk::Align::End,
                         set_icon_name: Some("help-about-symbolic"),
                         set_has_tooltip: true,
-                        set_tooltip_text: Some("Images will be randomly selected from this folder"),
+                        set_tooltip_text: Some("Images will be selected from this folder or archive"),
+                    },
+                },
+                
+                append = &gtk::Box {
+                    set_spacing: 5,
+
+                    append = &gtk::Button {
+                        set_hexpand: true,
+                        set_icon_name: "go-down-symbolic",
+                        set_tooltip_text: watch!(Some(model.get_folder_text().as_str())),
+
+                        set_child = Some(&gtk::Label) {
+                            set_ellipsize: gtk::pango::EllipsizeMode::Start,
+                            set_max_width_chars: 20,
+                            set_label: watch!(&model.get_folder_text()),
+                        },
+
+                        connect_clicked(sender) => move |_| {
+                            send!(sender, SettingsPageMsg::SelectFolder);
+                        },
+                    },
+
+                    append = &gtk::MenuButton {
+                        set_icon_name: "document-open-recent-symbolic",
+                        set_tooltip_text: Some("Recent folders"),
+
+                        set_popover = Some(&gtk::Popover) {
+                            set_child = Some(&gtk::Box) {
+                                set_orientation: gtk::Orientation::Vertical,
+                                set_spacing: 5,
+
+                                append = &gtk::Label {
+                                    set_visible: watch!(model.recent_items.is_empty()),
+                                    set_label: "No recent folders",
+                                },
+
+                                append = &gtk::Box {
+                                    set_orientation: gtk::Orientation::Vertical,
+                                    factory!(model.recent_items),
+                                },
+                            }
+                        }
+                    },
+
+                    append = &gtk::Button {
+                        set_icon_name: "package-x-generic-symbolic",
+                        set_tooltip_text: Some("Select a ZIP or CBZ archive"),
+
+                        connect_clicked(sender) => move |_| {
+                            send!(sender, SettingsPageMsg::SelectArchive);
+                        },
+                    },
+
+                    append = &gtk::Button {
+                        set_icon_name: "view-refresh-symbolic",
+                        set_tooltip_text: Some("Rescan the folder"),
+                        set_sensitive: watch!(!model.scanning()),
+
+                        connect_clicked(sender) => move |_| {
+                            send!(sender, SettingsPageMsg::Rescan);
+                        },
+                    },
+                },
+
+                append = &gtk::ProgressBar {
+                    set_visible: watch!(model.scanning()),
+                    set_fraction: watch!(model.get_scan_fraction()),
+                },
+
+                append = &gtk::CheckButton {
+                    set_label: Some("Watch folder for new images"),
+                    set_active: model.settings.watch_folder,
+                    connect_toggled(sender) => move |check_button| {
+                        send!(sender, SettingsPageMsg::WatchFolderChanged(check_button.is_active()));
+                    },
+                },
+            },
+            append = &gtk::Box {
+                set_visible: watch!(model.settings.source == SessionSource::List),
+                set_orientation: gtk::Orientation::Vertical,
+                set_spacing: 5,
+
+                append = &gtk::Box {
+                    set_spacing: 5,
+                    set_homogeneous: true,
+
+                    append = &gtk::Label {
+                        set_halign: gtk::Align::Start,
+                        set_label: "Select image list",
+                    },
+                    append = &gtk::Image {
+                        set_halign: gtk::Align::End,
+                        set_icon_name: Some("help-about-symbolic"),
+                        set_has_tooltip: true,
+                        set_tooltip_text: Some("A text or M3U file with one image path or glob pattern per line"),
                     },
                 },
                 
                 append = &gtk::Button {
                     set_icon_name: "go-down-symbolic",
+                    set_tooltip_text: watch!(Some(model.get_list_file_text().as_str())),
 
                     set_child = Some(&gtk::Label) {
-                        set_ellipsize: gtk::pango::EllipsizeMode::End,
-                        set_max_width_chars: 5,
-                        set_label: watch!(&model.get_folder_text()),
+                        set_ellipsize: gtk::pango::EllipsizeMode::Start,
+                        set_max_width_chars: 20,
+                        set_label: watch!(&model.get_list_file_text()),
                     },
 
                     connect_clicked(sender) => move |_| {
-                        send!(sender, SettingsPageMsg::SelectFolder);
+                        send!(sender, SettingsPageMsg::SelectListFile);
+                    },
+                },
+            },
+            append = &gtk::Box {
+                set_visible: watch!(model.settings.source == SessionSource::Server),
+                set_orientation: gtk::Orientation::Vertical,
+                set_spacing: 5,
+
+                append = &gtk::Box {
+                    set_spacing: 5,
+                    set_homogeneous: true,
+
+                    append = &gtk::Label {
+                        set_halign: gtk::Align::Start,
+                        set_label: "Server address",
+                    },
+                    append = &gtk::Image {
+                        set_halign: gtk::Align::End,
+                        set_icon_name: Some("help-about-symbolic"),
+                        set_has_tooltip: true,
+                        set_tooltip_text: Some("Address of a JSON list of image URLs, selected images are downloaded before the session starts"),
+                    },
+                },
+                append = &gtk::Entry {
+                    set_placeholder_text: Some("http://server/images.json"),
+                    set_text: &model.settings.server_url,
+                    connect_changed(sender) => move |entry| {
+                        send!(sender, SettingsPageMsg::ServerUrlChanged(entry.text().to_string()));
                     },
                 },
             },
@@ -155,11 +817,12 @@ impl Widgets<SettingsPage, App> for SettingsPageWidgets {
                         set_halign: gtk::Align::End,
                         set_icon_name: Some("help-about-symbolic"),
                         set_has_tooltip: true,
-                        set_tooltip_text: Some("Select how many images that should be randomly selected from the folder"),
+                        set_tooltip_text: Some("Select how many images that should be selected from the folder"),
                     },
                 },
 
                 append: images = &gtk::ComboBoxText {
+                    set_sensitive: watch!(!model.settings.endless && model.settings.time_budget.is_none()),
                     connect_changed(sender) => move |combo_box| {
                         if let Some(id) = combo_box.active_id() {
                             let value = id.parse::<usize>().unwrap_or(5);
@@ -167,6 +830,178 @@ impl Widgets<SettingsPage, App> for SettingsPageWidgets {
                         }
                     },
                 },
+
+                append = &gtk::CheckButton {
+                    set_label: Some("Endless, keep drawing until stopped"),
+                    set_active: model.settings.endless,
+                    connect_toggled(sender) => move |check_button| {
+                        send!(sender, SettingsPageMsg::EndlessChanged(check_button.is_active()));
+                    },
+                },
+            },
+            append = &gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+                set_spacing: 5,
+                
+                append = &gtk::Box {
+                    set_spacing: 5,
+                    set_homogeneous: true,
+
+                    append = &gtk::Label {
+                        set_halign: gtk::Align::Start,
+                        set_label: "Tags",
+                    },
+                    append = &gtk::Image {
+                        set_halign: gtk::Align::End,
+                        set_icon_name: Some("help-about-symbolic"),
+                        set_has_tooltip: true,
+                        set_tooltip_text: Some("Only use images matching these tags, for example: dynamic AND NOT clothed"),
+                    },
+                },
+                append = &gtk::Entry {
+                    set_placeholder_text: Some("All images"),
+                    set_text: &model.settings.tag_query,
+                    connect_changed(sender) => move |entry| {
+                        send!(sender, SettingsPageMsg::TagQueryChanged(entry.text().to_string()));
+                    },
+                },
+            },
+            append = &gtk::Expander {
+                set_label: Some("Filters"),
+
+                set_child = Some(&gtk::Box) {
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_spacing: 5,
+                    set_margin_top: 10,
+
+                    append = &gtk::Box {
+                        set_spacing: 5,
+                        set_homogeneous: true,
+
+                        append = &gtk::Label {
+                            set_halign: gtk::Align::Start,
+                            set_label: "Orientation",
+                        },
+                        append: orientation = &gtk::ComboBoxText {
+                            connect_changed(sender) => move |combo_box| {
+                                if let Some(id) = combo_box.active_id() {
+                                    if let Some(value) = OrientationFilter::from_id(&id) {
+                                        send!(sender, SettingsPageMsg::OrientationChanged(value));
+                                    }
+                                }
+                            },
+                        },
+                    },
+                    append = &gtk::Box {
+                        set_spacing: 5,
+                        set_homogeneous: true,
+
+                        append = &gtk::Label {
+                            set_halign: gtk::Align::Start,
+                            set_label: "Minimum width",
+                        },
+                        append = &gtk::SpinButton {
+                            set_adjustment: &gtk::Adjustment::new(model.settings.min_width as f64, 0.0, 20000.0, 100.0, 500.0, 0.0),
+                            connect_value_changed(sender) => move |spin_button| {
+                                send!(sender, SettingsPageMsg::MinWidthChanged(spin_button.value() as u32));
+                            },
+                        },
+                    },
+                    append = &gtk::Box {
+                        set_spacing: 5,
+                        set_homogeneous: true,
+
+                        append = &gtk::Label {
+                            set_halign: gtk::Align::Start,
+                            set_label: "Minimum height",
+                        },
+                        append = &gtk::SpinButton {
+                            set_adjustment: &gtk::Adjustment::new(model.settings.min_height as f64, 0.0, 20000.0, 100.0, 500.0, 0.0),
+                            connect_value_changed(sender) => move |spin_button| {
+                                send!(sender, SettingsPageMsg::MinHeightChanged(spin_button.value() as u32));
+                            },
+                        },
+                    },
+                    append = &gtk::Box {
+                        set_spacing: 5,
+                        set_homogeneous: true,
+
+                        append = &gtk::Label {
+                            set_halign: gtk::Align::Start,
+                            set_label: "Aspect ratio",
+                        },
+                        append = &gtk::Image {
+                            set_halign: gtk::Align::End,
+                            set_icon_name: Some("help-about-symbolic"),
+                            set_has_tooltip: true,
+                            set_tooltip_text: Some("Width divided by height, 0 means no limit"),
+                        },
+                    },
+                    append = &gtk::Box {
+                        set_spacing: 5,
+                        set_homogeneous: true,
+
+                        append = &gtk::SpinButton {
+                            set_digits: 2,
+                            set_adjustment: &gtk::Adjustment::new(model.settings.min_aspect_ratio.unwrap_or(0.0), 0.0, 10.0, 0.05, 0.5, 0.0),
+                            connect_value_changed(sender) => move |spin_button| {
+                                let value = match spin_button.value() {
+                                    value if value > 0.0 => Some(value),
+                                    _ => None,
+                                };
+                                send!(sender, SettingsPageMsg::MinAspectRatioChanged(value));
+                            },
+                        },
+                        append = &gtk::SpinButton {
+                            set_digits: 2,
+                            set_adjustment: &gtk::Adjustment::new(model.settings.max_aspect_ratio.unwrap_or(0.0), 0.0, 10.0, 0.05, 0.5, 0.0),
+                            connect_value_changed(sender) => move |spin_button| {
+                                let value = match spin_button.value() {
+                                    value if value > 0.0 => Some(value),
+                                    _ => None,
+                                };
+                                send!(sender, SettingsPageMsg::MaxAspectRatioChanged(value));
+                            },
+                        },
+                    },
+                    append = &gtk::CheckButton {
+                        set_label: Some("Skip near-duplicates"),
+                        set_tooltip_text: Some("Only show one image of each group of similar looking images"),
+                        set_active: model.settings.skip_duplicates,
+                        connect_toggled(sender) => move |check_button| {
+                            send!(sender, SettingsPageMsg::SkipDuplicatesChanged(check_button.is_active()));
+                        },
+                    },
+                },
+            },
+            append = &gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+                set_spacing: 5,
+                
+                append = &gtk::Box {
+                    set_spacing: 5,
+                    set_homogeneous: true,
+
+                    append = &gtk::Label {
+                        set_halign: gtk::Align::Start,
+                        set_label: "Order",
+                    },
+                    append = &gtk::Image {
+                        set_halign: gtk::Align::End,
+                        set_icon_name: Some("help-about-symbolic"),
+                        set_has_tooltip: true,
+                        set_tooltip_text: Some("Select in which order images are picked from the folder, continue resumes where the last session in this folder ended"),
+                    },
+                },
+                append: selection_mode = &gtk::ComboBoxText {
+                    connect_changed(sender) => move |combo_box| {
+                        if let Some(id) = combo_box.active_id() {
+                            if let Some(value) = SelectionMode::from_id(&id) {
+                                send!(sender, SettingsPageMsg::SelectionModeChanged(value));
+                            }
+                        }
+                    },
+                },
             },
             append = &gtk::Box {
                 set_orientation: gtk::Orientation::Vertical,
@@ -198,6 +1033,47 @@ impl Widgets<SettingsPage, App> for SettingsPageWidgets {
                         }
                     },
                 },
+
+                append = &gtk::Box {
+                    set_spacing: 5,
+                    set_homogeneous: true,
+
+                    append = &gtk::CheckButton {
+                        set_label: Some("Advance automatically"),
+                        set_tooltip_text: Some("When unchecked the timer keeps running past the duration and shows the overtime, press next when you are ready"),
+                        set_active: model.settings.auto_advance,
+                        connect_toggled(sender) => move |check_button| {
+                            send!(sender, SettingsPageMsg::AutoAdvanceChanged(check_button.is_active()));
+                        },
+                    },
+
+                    append: timer_display = &gtk::ComboBoxText {
+                        connect_changed(sender) => move |combo_box| {
+                            if let Some(id) = combo_box.active_id() {
+                                if let Some(value) = TimerDisplay::from_id(&id) {
+                                    send!(sender, SettingsPageMsg::TimerDisplayChanged(value));
+                                }
+                            }
+                        },
+                    },
+                },
+
+                append = &gtk::CheckButton {
+                    set_label: Some("Pause when the window loses focus"),
+                    set_active: model.settings.pause_on_focus_loss,
+                    connect_toggled(sender) => move |check_button| {
+                        send!(sender, SettingsPageMsg::PauseOnFocusLossChanged(check_button.is_active()));
+                    },
+                },
+
+                append = &gtk::CheckButton {
+                    set_label: Some("Pause when the computer is idle"),
+                    set_tooltip_text: Some("Uses the idle hint from the login manager, not every desktop reports it"),
+                    set_active: model.settings.pause_when_idle,
+                    connect_toggled(sender) => move |check_button| {
+                        send!(sender, SettingsPageMsg::PauseWhenIdleChanged(check_button.is_active()));
+                    },
+                },
             },
             append = &gtk::Box {
                 set_orientation: gtk::Orientation::Vertical,
@@ -229,17 +1105,286 @@ impl Widgets<SettingsPage, App> for SettingsPageWidgets {
                         }
                     },
                 },
+
+                append = &gtk::Expander {
+                    set_label: Some("Intermission screen"),
+
+                    set_child = Some(&gtk::Box) {
+                        set_orientation: gtk::Orientation::Vertical,
+                        set_spacing: 5,
+
+                        append = &gtk::CheckButton {
+                            set_label: Some("Show a random prompt"),
+                            set_active: model.settings.intermission_prompt,
+                            connect_toggled(sender) => move |check_button| {
+                                send!(sender, SettingsPageMsg::IntermissionPromptChanged(check_button.is_active()));
+                            },
+                        },
+
+                        append = &gtk::ScrolledWindow {
+                            set_min_content_height: 100,
+                            set_sensitive: watch!(model.settings.intermission_prompt),
+
+                            set_child: prompts_view = Some(&gtk::TextView) {
+                                set_wrap_mode: gtk::WrapMode::WordChar,
+                                set_tooltip_text: Some("One prompt per line"),
+                            },
+                        },
+
+                        append = &gtk::CheckButton {
+                            set_label: Some("Preview the next image"),
+                            set_active: model.settings.intermission_preview,
+                            connect_toggled(sender) => move |check_button| {
+                                send!(sender, SettingsPageMsg::IntermissionPreviewChanged(check_button.is_active()));
+                            },
+                        },
+
+                        append = &gtk::CheckButton {
+                            set_label: Some("Allow skipping the intermission"),
+                            set_active: model.settings.intermission_skip,
+                            connect_toggled(sender) => move |check_button| {
+                                send!(sender, SettingsPageMsg::IntermissionSkipChanged(check_button.is_active()));
+                            },
+                        },
+                    },
+                },
             },
-            append = &gtk::Button {
-                set_label: "Start",
-                connect_clicked(sender) => move |_| {
-                    send!(sender, SettingsPageMsg::Start);
+            append = &gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+                set_spacing: 5,
+                
+                append = &gtk::Box {
+                    set_spacing: 5,
+                    set_homogeneous: true,
+
+                    append = &gtk::Label {
+                        set_halign: gtk::Align::Start,
+                        set_label: "Memory drawing",
+                    },
+                    append = &gtk::Image {
+                        set_halign: gtk::Align::End,
+                        set_icon_name: Some("help-about-symbolic"),
+                        set_has_tooltip: true,
+                        set_tooltip_text: Some("Hide the image when the duration is over and keep drawing from memory, then reveal it again to compare. Needs a duration and auto-advance"),
+                    },
+                },
+                append: memory_time = &gtk::ComboBoxText {
+                    connect_changed(sender) => move |combo_box| {
+                        if let Some(id) = combo_box.active_id() {
+                            let value = match id.parse::<u64>() {
+                                Ok(0) | Err(_) => None,
+                                Ok(value) => Some(value),
+                            };
+                            send!(sender, SettingsPageMsg::MemoryTimeChanged(value));
+                        }
+                    },
+                },
+            },
+            append = &gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+                set_spacing: 5,
+                set_sensitive: watch!(model.settings.memory_time.is_some()),
+
+                append = &gtk::Box {
+                    set_spacing: 5,
+                    set_homogeneous: true,
+
+                    append = &gtk::Label {
+                        set_halign: gtk::Align::Start,
+                        set_label: "Reveal",
+                    },
+                    append = &gtk::Image {
+                        set_halign: gtk::Align::End,
+                        set_icon_name: Some("help-about-symbolic"),
+                        set_has_tooltip: true,
+                        set_tooltip_text: Some("How long the image is shown again after memory drawing before the session moves on"),
+                    },
+                },
+                append: reveal_time = &gtk::ComboBoxText {
+                    connect_changed(sender) => move |combo_box| {
+                        if let Some(id) = combo_box.active_id() {
+                            if let Ok(value) = id.parse::<u64>() {
+                                send!(sender, SettingsPageMsg::RevealTimeChanged(value));
+                            }
+                        }
+                    },
+                },
+            },
+            append = &gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+                set_spacing: 5,
+                
+                append = &gtk::Box {
+                    set_spacing: 5,
+                    set_homogeneous: true,
+
+                    append = &gtk::Label {
+                        set_halign: gtk::Align::Start,
+                        set_label: "Time budget",
+                    },
+                    append = &gtk::Image {
+                        set_halign: gtk::Align::End,
+                        set_icon_name: Some("help-about-symbolic"),
+                        set_has_tooltip: true,
+                        set_tooltip_text: Some("Plan the number of images and their durations to fit into the time you have"),
+                    },
+                },
+                append: time_budget = &gtk::ComboBoxText {
+                    set_sensitive: watch!(!model.settings.endless),
+                    connect_changed(sender) => move |combo_box| {
+                        if let Some(id) = combo_box.active_id() {
+                            let value = match id.parse::<u64>() {
+                                Ok(0) | Err(_) => None,
+                                Ok(value) => Some(value),
+                            };
+                            send!(sender, SettingsPageMsg::TimeBudgetChanged(value));
+                        }
+                    },
+                },
+                append: pose_curve = &gtk::ComboBoxText {
+                    set_visible: watch!(model.settings.time_budget.is_some()),
+                    set_sensitive: watch!(!model.settings.endless),
+                    connect_changed(sender) => move |combo_box| {
+                        if let Some(id) = combo_box.active_id() {
+                            if let Some(value) = PoseCurve::from_id(&id) {
+                                send!(sender, SettingsPageMsg::PoseCurveChanged(value));
+                            }
+                        }
+                    },
+                },
+            },
+            append = &gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+                set_spacing: 5,
+                
+                append = &gtk::Box {
+                    set_spacing: 5,
+                    set_homogeneous: true,
+
+                    append = &gtk::Label {
+                        set_halign: gtk::Align::Start,
+                        set_label: "Exercise prompts",
+                    },
+                    append = &gtk::Image {
+                        set_halign: gtk::Align::End,
+                        set_icon_name: Some("help-about-symbolic"),
+                        set_has_tooltip: true,
+                        set_tooltip_text: Some("Show an exercise next to each image, picked from the deck by weight. The same session code gives the same prompts"),
+                    },
+                },
+                append = &gtk::CheckButton {
+                    set_label: Some("Show a prompt with each image"),
+                    set_active: model.settings.exercise_prompts,
+                    connect_toggled(sender) => move |check_button| {
+                        send!(sender, SettingsPageMsg::ExercisePromptsChanged(check_button.is_active()));
+                    },
+                },
+                append = &gtk::Expander {
+                    set_label: Some("Prompt deck"),
+                    set_sensitive: watch!(model.settings.exercise_prompts),
+
+                    set_child = Some(&gtk::ScrolledWindow) {
+                        set_min_content_height: 150,
+
+                        set_child: deck_view = Some(&gtk::TextView) {
+                            set_wrap_mode: gtk::WrapMode::WordChar,
+                            set_tooltip_text: Some("One prompt per line, starting with its weight, for example \"3 Use only 5 lines\""),
+                        },
+                    },
+                },
+            },
+            append = &gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+                set_spacing: 5,
+                
+                append = &gtk::Box {
+                    set_spacing: 5,
+                    set_homogeneous: true,
+
+                    append = &gtk::Label {
+                        set_halign: gtk::Align::Start,
+                        set_label: "Session code",
+                    },
+                    append = &gtk::Image {
+                        set_halign: gtk::Align::End,
+                        set_icon_name: Some("help-about-symbolic"),
+                        set_has_tooltip: true,
+                        set_tooltip_text: Some("Sessions started with the same code and folder will show the same images in the same order"),
+                    },
+                },
+                append = &gtk::Entry {
+                    set_placeholder_text: Some("Random"),
+                    set_text: &model.get_seed_text(),
+                    connect_changed(sender) => move |entry| {
+                        send!(sender, SettingsPageMsg::SeedChanged(parse_seed(&entry.text())));
+                    },
+                },
+            },
+            append = &gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+                set_spacing: 5,
+
+                append = &gtk::Label {
+                    set_label: watch!(&model.get_pool_text()),
+                },
+
+                append = &gtk::Box {
+                    set_visible: watch!(model.pool_too_small()),
+                    set_halign: gtk::Align::Center,
+                    set_spacing: 5,
+
+                    append = &gtk::Image {
+                        set_icon_name: Some("dialog-warning-symbolic"),
+                    },
+                    append = &gtk::Label {
+                        set_label: watch!(&model.get_pool_warning_text()),
+                    },
+                },
+            },
+            append = &gtk::Box {
+                set_spacing: 5,
+                set_homogeneous: true,
+
+                append = &gtk::Button {
+                    set_label: "Browse",
+                    set_tooltip_text: Some("Browse the images and pick which ones to use"),
+                    connect_clicked(sender) => move |_| {
+                        send!(sender, SettingsPageMsg::Browse);
+                    },
+                },
+
+                append = &gtk::Button {
+                    set_label: "Gallery",
+                    set_tooltip_text: Some("Browse drawings from past sessions"),
+                    connect_clicked(sender) => move |_| {
+                        send!(sender, SettingsPageMsg::ShowGallery);
+                    },
+                },
+
+                append = &gtk::Button {
+                    set_label: "Start",
+                    connect_clicked(sender) => move |_| {
+                        send!(sender, SettingsPageMsg::Start);
+                    },
                 },
             },
         }
     }
 
     fn post_init() {
+        let target = drop_target();
+        let drop_sender = sender.clone();
+        target.connect_drop(move |_, value, _, _| {
+            let paths = dropped_paths(value);
+            if paths.is_empty() {
+                return false;
+            }
+
+            send!(drop_sender, SettingsPageMsg::FilesDropped(paths));
+            true
+        });
+        settings_box.add_controller(&target);
+
         images.append(Some("5"), "5");
         images.append(Some("10"), "10");
         images.append(Some("15"), "15");
@@ -247,6 +1392,25 @@ impl Widgets<SettingsPage, App> for SettingsPageWidgets {
         images.append(Some("60"), "60");
         images.set_active_id(Some("5"));
 
+        source.append(Some(SessionSource::Folder.id()), "Folder");
+        source.append(Some(SessionSource::List.id()), "Image list");
+        source.append(Some(SessionSource::Server.id()), "Image server");
+        source.append(Some(SessionSource::Favourites.id()), "Favourites");
+        source.set_active_id(Some(model.settings.source.id()));
+
+        orientation.append(Some(OrientationFilter::Any.id()), "Any");
+        orientation.append(Some(OrientationFilter::Portrait.id()), "Portrait");
+        orientation.append(Some(OrientationFilter::Landscape.id()), "Landscape");
+        orientation.append(Some(OrientationFilter::Square.id()), "Square");
+        orientation.set_active_id(Some(model.settings.orientation.id()));
+
+        selection_mode.append(Some(SelectionMode::Random.id()), "Random");
+        selection_mode.append(Some(SelectionMode::Alphabetical.id()), "Alphabetical");
+        selection_mode.append(Some(SelectionMode::Natural.id()), "Natural number order");
+        selection_mode.append(Some(SelectionMode::Modified.id()), "Modified time");
+        selection_mode.append(Some(SelectionMode::Continue.id()), "Continue where I left off");
+        selection_mode.set_active_id(Some(model.settings.selection_mode.id()));
+
         duration.append(Some("0"), "None");
         duration.append(Some("15"), "15 seconds");
         duration.append(Some("30"), "30 seconds");
@@ -263,5 +1427,61 @@ impl Widgets<SettingsPage, App> for SettingsPageWidgets {
         pause.append(Some("30"), "30 seconds");
         pause.append(Some("60"), "1 minute");
         pause.set_active_id(Some("0"));
+
+        memory_time.append(Some("0"), "None");
+        memory_time.append(Some("30"), "30 seconds");
+        memory_time.append(Some("60"), "1 minute");
+        memory_time.append(Some("120"), "2 minutes");
+        memory_time.append(Some("300"), "5 minutes");
+        memory_time.set_active_id(Some("0"));
+
+        reveal_time.append(Some("5"), "5 seconds");
+        reveal_time.append(Some("15"), "15 seconds");
+        reveal_time.append(Some("30"), "30 seconds");
+        reveal_time.append(Some("60"), "1 minute");
+        reveal_time.set_active_id(Some("15"));
+
+        let prompts_buffer = prompts_view.buffer();
+        prompts_buffer.set_text(&load_intermission_prompts().join("\n"));
+
+        let prompts_sender = sender.clone();
+        prompts_buffer.connect_changed(move |buffer| {
+            let text = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+            send!(prompts_sender, SettingsPageMsg::IntermissionPromptsEdited(text.to_string()));
+        });
+
+        let deck: Vec<String> = load_prompt_deck()
+            .iter()
+            .map(format_prompt_card)
+            .collect();
+
+        let deck_buffer = deck_view.buffer();
+        deck_buffer.set_text(&deck.join("\n"));
+
+        let deck_sender = sender.clone();
+        deck_buffer.connect_changed(move |buffer| {
+            let text = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+            send!(deck_sender, SettingsPageMsg::PromptDeckEdited(text.to_string()));
+        });
+
+        timer_display.append(Some(TimerDisplay::Countdown.id()), "Count down");
+        timer_display.append(Some(TimerDisplay::Elapsed.id()), "Count up");
+        timer_display.set_active_id(Some(model.settings.timer_display.id()));
+
+        time_budget.append(Some("0"), "None");
+        time_budget.append(Some("600"), "10 minutes");
+        time_budget.append(Some("900"), "15 minutes");
+        time_budget.append(Some("1800"), "30 minutes");
+        time_budget.append(Some("2700"), "45 minutes");
+        time_budget.append(Some("3600"), "1 hour");
+        time_budget.append(Some("5400"), "1.5 hours");
+        time_budget.append(Some("7200"), "2 hours");
+        time_budget.set_active_id(Some("0"));
+
+        pose_curve.append(Some(PoseCurve::Constant.id()), "Same duration for every image");
+        pose_curve.append(Some(PoseCurve::ShortToLong.id()), "Short to long");
+        pose_curve.append(Some(PoseCurve::LongToShort.id()), "Long to short");
+        pose_curve.append(Some(PoseCurve::WarmUp.id()), "Warm up, long, cool down");
+        pose_curve.set_active_id(Some(model.settings.pose_curve.id()));
     }
 }
\ No newline at end of file
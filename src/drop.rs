@@ -0,0 +1,40 @@
+use gtk::prelude::*;
+use gtk::{gdk, gio, glib};
+
+pub fn drop_target() -> gtk::DropTarget {
+    let target = gtk::DropTarget::new(glib::Type::INVALID, gdk::DragAction::COPY);
+    target.set_types(&[gdk::FileList::static_type(), String::static_type()]);
+    target
+}
+
+pub fn dropped_paths(value: &glib::Value) -> Vec<String> {
+    if let Ok(files) = value.get::<gdk::FileList>() {
+        return files
+            .files()
+            .iter()
+            .filter_map(file_path)
+            .collect();
+    }
+
+    if let Ok(text) = value.get::<String>() {
+        return parse_uri_list(&text)
+            .into_iter()
+            .map(|uri| gio::File::for_uri(&uri))
+            .filter_map(|file| file_path(&file))
+            .collect();
+    }
+
+    vec![]
+}
+
+fn parse_uri_list(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.to_owned())
+        .collect()
+}
+
+fn file_path(file: &gio::File) -> Option<String> {
+    file.path()?.to_str().map(String::from)
+}
@@ -0,0 +1,279 @@
+use std::collections::HashMap;
+
+use crate::storage::{read_lines, write_lines};
+
+pub const HASH_WIDTH: usize = 9;
+pub const HASH_HEIGHT: usize = 8;
+pub const DUPLICATE_THRESHOLD: u32 = 10;
+
+const HASHES_FILE: &str = "hashes";
+
+pub fn difference_hash(pixels: &[u8], rowstride: usize, channels: usize) -> Option<u64> {
+    let mut hash = 0;
+
+    for y in 0..HASH_HEIGHT {
+        for x in 0..HASH_WIDTH - 1 {
+            let left = brightness(pixels, y * rowstride + x * channels)?;
+            let right = brightness(pixels, y * rowstride + (x + 1) * channels)?;
+
+            hash <<= 1;
+            if left > right {
+                hash |= 1;
+            }
+        }
+    }
+
+    Some(hash)
+}
+
+fn brightness(pixels: &[u8], offset: usize) -> Option<u32> {
+    let pixel = pixels.get(offset..offset + 3)?;
+
+    Some(pixel[0] as u32 * 299 + pixel[1] as u32 * 587 + pixel[2] as u32 * 114)
+}
+
+pub fn hash_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+pub fn find_clusters(hashes: &[(String, u64)], threshold: u32) -> Vec<Vec<String>> {
+    let mut clusters = DuplicateClusters::new(threshold);
+
+    for (image, hash) in hashes {
+        match clusters.find(*hash) {
+            Some(cluster) => clusters.join(cluster, image),
+            None => {
+                clusters.add(image, *hash);
+            },
+        }
+    }
+
+    clusters.duplicates()
+}
+
+struct Node {
+    hash: u64,
+    cluster: usize,
+    children: HashMap<u32, usize>,
+}
+
+pub struct DuplicateClusters {
+    threshold: u32,
+    nodes: Vec<Node>,
+    clusters: Vec<Vec<String>>,
+}
+
+impl DuplicateClusters {
+    pub fn new(threshold: u32) -> Self {
+        Self {
+            threshold,
+            nodes: vec![],
+            clusters: vec![],
+        }
+    }
+
+    pub fn find(&self, hash: u64) -> Option<usize> {
+        let mut best: Option<(u32, usize)> = None;
+        let mut pending = match self.nodes.is_empty() {
+            true => vec![],
+            false => vec![0],
+        };
+
+        while let Some(position) = pending.pop() {
+            let node = &self.nodes[position];
+            let distance = hash_distance(hash, node.hash);
+
+            let closer = match best {
+                Some((value, _)) => distance < value,
+                None => true,
+            };
+
+            if distance <= self.threshold && closer {
+                best = Some((distance, node.cluster));
+            }
+
+            for (edge, child) in &node.children {
+                if edge.abs_diff(distance) <= self.threshold {
+                    pending.push(*child);
+                }
+            }
+        }
+
+        best.map(|(_, cluster)| cluster)
+    }
+
+    pub fn add(&mut self, image: &str, hash: u64) -> usize {
+        let cluster = self.clusters.len();
+        self.clusters.push(vec![image.to_owned()]);
+
+        let node = self.nodes.len();
+        self.nodes.push(Node {
+            hash,
+            cluster,
+            children: HashMap::new(),
+        });
+
+        let mut position = 0;
+        while position != node {
+            let distance = hash_distance(hash, self.nodes[position].hash);
+
+            match self.nodes[position].children.get(&distance) {
+                Some(child) => position = *child,
+                None => {
+                    self.nodes[position].children.insert(distance, node);
+                    break;
+                },
+            }
+        }
+
+        cluster
+    }
+
+    pub fn join(&mut self, cluster: usize, image: &str) {
+        if let Some(images) = self.clusters.get_mut(cluster) {
+            images.push(image.to_owned());
+        }
+    }
+
+    pub fn duplicates(&self) -> Vec<Vec<String>> {
+        let mut clusters: Vec<Vec<String>> = self.clusters
+            .iter()
+            .filter(|cluster| cluster.len() > 1)
+            .cloned()
+            .collect();
+
+        clusters.sort();
+
+        clusters
+    }
+}
+
+pub struct HashCache {
+    hashes: HashMap<String, (u64, u64)>,
+}
+
+impl HashCache {
+    pub fn load() -> Self {
+        let mut hashes = HashMap::new();
+
+        for line in read_lines(HASHES_FILE) {
+            let values: Vec<&str> = line.splitn(3, '\t').collect();
+
+            if let [hash, modified, path] = values[..] {
+                if let (Ok(hash), Ok(modified)) = (u64::from_str_radix(hash, 16), modified.parse::<u64>()) {
+                    hashes.insert(path.to_owned(), (hash, modified));
+                }
+            }
+        }
+
+        Self {
+            hashes,
+        }
+    }
+
+    pub fn save(&self) {
+        let mut lines: Vec<String> = self.hashes
+            .iter()
+            .map(|(path, (hash, modified))| format!("{:016x}\t{}\t{}", hash, modified, path))
+            .collect();
+
+        lines.sort();
+
+        if let Err(error) = write_lines(HASHES_FILE, &lines) {
+            eprintln!("Failed to save image hashes: {}", error);
+        }
+    }
+
+    pub fn hash<F: Fn(&str) -> Option<u64>>(&mut self, image: &str, modified: u64, hasher: F) -> Option<u64> {
+        if let Some((hash, cached_modified)) = self.hashes.get(image) {
+            if *cached_modified == modified {
+                return Some(*hash);
+            }
+        }
+
+        let hash = hasher(image)?;
+        self.hashes.insert(image.to_owned(), (hash, modified));
+
+        Some(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hashes(values: &[(&str, u64)]) -> Vec<(String, u64)> {
+        values.iter().map(|(image, hash)| (image.to_string(), *hash)).collect()
+    }
+
+    #[test]
+    fn groups_near_duplicates() {
+        let clusters = find_clusters(&hashes(&[("a", 0), ("b", 0b111), ("c", u64::MAX), ("d", u64::MAX ^ 1)]), 3);
+        assert_eq!(clusters, vec![vec!["a", "b"], vec!["c", "d"]]);
+    }
+
+    #[test]
+    fn does_not_chain_distinct_images() {
+        let clusters = find_clusters(&hashes(&[("a", 0), ("b", 0xff), ("c", 0xffff)]), 10);
+        assert_eq!(clusters, vec![vec!["a", "b"]]);
+    }
+
+    #[test]
+    fn joins_the_closest_representative() {
+        let mut clusters = DuplicateClusters::new(10);
+        clusters.add("a", 0);
+        clusters.add("b", 0xfffff);
+
+        assert_eq!(clusters.find(0xffff), Some(1));
+        assert_eq!(clusters.find(0xf), Some(0));
+        assert_eq!(clusters.find(u64::MAX), None);
+    }
+
+    #[test]
+    fn tree_search_matches_linear_search() {
+        let mut value: u64 = 0x9e3779b97f4a7c15;
+        let mut values = vec![];
+
+        for _ in 0..500 {
+            value ^= value << 13;
+            value ^= value >> 7;
+            value ^= value << 17;
+            values.push(value & 0xffff_ffff);
+        }
+
+        let mut clusters = DuplicateClusters::new(6);
+        let mut representatives: Vec<u64> = vec![];
+
+        for (position, hash) in values.iter().enumerate() {
+            let linear = representatives
+                .iter()
+                .enumerate()
+                .map(|(cluster, value)| (hash_distance(*hash, *value), cluster))
+                .filter(|(distance, _)| *distance <= 6)
+                .min()
+                .map(|(_, cluster)| cluster);
+            let tree = clusters.find(*hash);
+
+            assert_eq!(tree.map(|cluster| hash_distance(*hash, representatives[cluster])), linear.map(|cluster| hash_distance(*hash, representatives[cluster])));
+
+            if tree.is_none() {
+                clusters.add(&position.to_string(), *hash);
+                representatives.push(*hash);
+            }
+        }
+    }
+
+    #[test]
+    fn difference_hash_compares_neighbouring_pixels() {
+        let mut pixels = vec![];
+        for _ in 0..HASH_HEIGHT {
+            for x in 0..HASH_WIDTH {
+                let value = if x % 2 == 0 { 255 } else { 0 };
+                pixels.extend_from_slice(&[value, value, value]);
+            }
+        }
+
+        assert_eq!(difference_hash(&pixels, HASH_WIDTH * 3, 3), Some(0xaaaa_aaaa_aaaa_aaaa));
+        assert_eq!(difference_hash(&pixels[..10], HASH_WIDTH * 3, 3), None);
+    }
+}
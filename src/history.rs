@@ -0,0 +1,217 @@
+use std::time::SystemTime;
+use serde_json::{json, Value};
+
+use crate::session::Session;
+use crate::storage::{read_lines, write_lines};
+
+const HISTORY_FILE: &str = "history";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrawingPair {
+    pub reference: String,
+    pub drawing: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionRecord {
+    pub finished: u64,
+    pub seed: u64,
+    pub source: String,
+    pub images: usize,
+    pub time_spent: usize,
+    pub drawings: Vec<DrawingPair>,
+}
+
+impl SessionRecord {
+    pub fn from_session(session: &Session) -> Self {
+        let finished = match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(value) => value.as_secs(),
+            Err(_) => 0,
+        };
+
+        let mut record = Self {
+            finished,
+            seed: session.seed,
+            source: session.source.clone(),
+            images: session.images_done(),
+            time_spent: session.total_time_spent(),
+            drawings: vec![],
+        };
+
+        for reference in &session.images {
+            if let Some(drawing) = session.drawings.get(reference) {
+                record.attach_drawing(reference, drawing);
+            }
+        }
+
+        record
+    }
+
+    pub fn attach_drawing(&mut self, reference: &str, drawing: &str) {
+        let pair = DrawingPair {
+            reference: reference.to_owned(),
+            drawing: drawing.to_owned(),
+        };
+
+        match self.drawings.iter_mut().find(|pair| pair.reference == reference) {
+            Some(value) => *value = pair,
+            None => self.drawings.push(pair),
+        }
+    }
+
+    fn from_json(value: &Value) -> Option<Self> {
+        let mut record = Self {
+            finished: value.get("finished")?.as_u64()?,
+            seed: value.get("seed")?.as_u64()?,
+            source: value.get("source")?.as_str()?.to_owned(),
+            images: value.get("images")?.as_u64()? as usize,
+            time_spent: value.get("time_spent")?.as_u64()? as usize,
+            drawings: vec![],
+        };
+
+        if let Some(Value::Array(pairs)) = value.get("drawings") {
+            for pair in pairs {
+                let reference = pair.get("reference").and_then(|value| value.as_str());
+                let drawing = pair.get("drawing").and_then(|value| value.as_str());
+
+                if let (Some(reference), Some(drawing)) = (reference, drawing) {
+                    record.attach_drawing(reference, drawing);
+                }
+            }
+        }
+
+        Some(record)
+    }
+
+    fn to_json(&self) -> Value {
+        let drawings: Vec<Value> = self.drawings
+            .iter()
+            .map(|pair| json!({
+                "reference": pair.reference,
+                "drawing": pair.drawing,
+            }))
+            .collect();
+
+        json!({
+            "finished": self.finished,
+            "seed": self.seed,
+            "source": self.source,
+            "images": self.images,
+            "time_spent": self.time_spent,
+            "drawings": drawings,
+        })
+    }
+
+    pub fn drawing(&self, reference: &str) -> Option<&str> {
+        self.drawings
+            .iter()
+            .find(|pair| pair.reference == reference)
+            .map(|pair| pair.drawing.as_str())
+    }
+}
+
+pub struct History {
+    records: Vec<SessionRecord>,
+}
+
+impl History {
+    pub fn load() -> Self {
+        let records = read_lines(HISTORY_FILE)
+            .iter()
+            .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            .filter_map(|value| SessionRecord::from_json(&value))
+            .collect();
+
+        Self::with_records(records)
+    }
+
+    pub fn with_records(records: Vec<SessionRecord>) -> Self {
+        let mut history = Self { records };
+        history.records.sort_by_key(|record| record.finished);
+        history
+    }
+
+    pub fn save(&self) {
+        let lines: Vec<String> = self.records
+            .iter()
+            .map(|record| record.to_json().to_string())
+            .collect();
+
+        if let Err(error) = write_lines(HISTORY_FILE, &lines) {
+            eprintln!("Failed to save history: {}", error);
+        }
+    }
+
+    pub fn records(&self) -> &[SessionRecord] {
+        &self.records
+    }
+
+    pub fn record(&mut self, record: SessionRecord) {
+        match self.records.iter_mut().find(|value| value.finished == record.finished && value.seed == record.seed) {
+            Some(value) => *value = record,
+            None => self.records.push(record),
+        }
+
+        self.records.sort_by_key(|record| record.finished);
+    }
+
+    pub fn drawings(&self) -> Vec<(&SessionRecord, &DrawingPair)> {
+        self.records
+            .iter()
+            .flat_map(|record| record.drawings.iter().map(move |pair| (record, pair)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record() -> SessionRecord {
+        let mut record = SessionRecord {
+            finished: 1_700_000_000,
+            seed: 123456,
+            source: "/pictures/poses".into(),
+            images: 2,
+            time_spent: 90,
+            drawings: vec![],
+        };
+
+        record.attach_drawing("/pictures/poses/a\tb.png", "/drawings/line\nbreak.png");
+        record
+    }
+
+    #[test]
+    fn records_survive_tabs_and_newlines() {
+        let record = record();
+        let line = record.to_json().to_string();
+
+        assert!(!line.contains('\n'));
+        assert_eq!(SessionRecord::from_json(&serde_json::from_str(&line).unwrap()), Some(record));
+    }
+
+    #[test]
+    fn attaching_replaces_the_previous_drawing() {
+        let mut record = record();
+        record.attach_drawing("/pictures/poses/a\tb.png", "/drawings/second.png");
+
+        assert_eq!(record.drawings.len(), 1);
+        assert_eq!(record.drawing("/pictures/poses/a\tb.png"), Some("/drawings/second.png"));
+    }
+
+    #[test]
+    fn incomplete_records_are_skipped() {
+        assert_eq!(SessionRecord::from_json(&json!({ "finished": 1 })), None);
+    }
+
+    #[test]
+    fn recording_replaces_the_same_session() {
+        let mut history = History::with_records(vec![record()]);
+        let mut updated = record();
+        updated.attach_drawing("/pictures/poses/c.png", "/drawings/c.png");
+        history.record(updated.clone());
+
+        assert_eq!(history.records(), &[updated]);
+        assert_eq!(history.drawings().len(), 2);
+    }
+}
@@ -0,0 +1,228 @@
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct Url {
+    pub host: String,
+    pub port: u16,
+    pub path: String,
+}
+
+impl Url {
+    pub fn parse(url: &str) -> Result<Self, String> {
+        let rest = match url.strip_prefix("http://") {
+            Some(value) => value,
+            None => return Err(format!("Only http:// addresses are supported: {}", url)),
+        };
+
+        let (authority, path) = match rest.find('/') {
+            Some(index) => (&rest[..index], &rest[index..]),
+            None => (rest, "/"),
+        };
+
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => match port.parse::<u16>() {
+                Ok(port) => (host, port),
+                Err(_) => return Err(format!("Invalid port in {}", url)),
+            },
+            None => (authority, 80),
+        };
+
+        if host.is_empty() {
+            return Err(format!("Missing host in {}", url));
+        }
+
+        Ok(Self {
+            host: host.to_owned(),
+            port,
+            path: path.to_owned(),
+        })
+    }
+}
+
+pub fn is_remote(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+pub fn resolve_url(base: &str, url: &str) -> String {
+    if is_remote(url) {
+        return url.to_owned();
+    }
+
+    if let Some(path) = url.strip_prefix('/') {
+        if let Ok(base) = Url::parse(base) {
+            return format!("http://{}:{}/{}", base.host, base.port, path);
+        }
+    }
+
+    match base.rfind('/') {
+        Some(index) if index > "http://".len() => format!("{}/{}", &base[..index], url),
+        _ => format!("{}/{}", base, url),
+    }
+}
+
+pub fn http_get(url: &str) -> Result<Vec<u8>, String> {
+    let parsed = Url::parse(url)?;
+
+    let address = match (parsed.host.as_str(), parsed.port).to_socket_addrs() {
+        Ok(mut value) => match value.next() {
+            Some(address) => address,
+            None => return Err(format!("Failed to resolve {}", parsed.host)),
+        },
+        Err(_) => return Err(format!("Failed to resolve {}", parsed.host)),
+    };
+
+    let mut stream = match TcpStream::connect_timeout(&address, CONNECT_TIMEOUT) {
+        Ok(value) => value,
+        Err(_) => return Err(format!("Failed to connect to {}", parsed.host)),
+    };
+
+    if stream.set_read_timeout(Some(READ_TIMEOUT)).is_err() {
+        return Err(format!("Failed to connect to {}", parsed.host));
+    }
+
+    let request = format!("GET {} HTTP/1.0\r\nHost: {}\r\nConnection: close\r\n\r\n", parsed.path, parsed.host);
+    if stream.write_all(request.as_bytes()).is_err() {
+        return Err(format!("Failed to send request to {}", parsed.host));
+    }
+
+    let mut response = vec![];
+    if stream.read_to_end(&mut response).is_err() {
+        return Err(format!("Failed to read response from {}", parsed.host));
+    }
+
+    parse_response(&response, url)
+}
+
+fn parse_response(response: &[u8], url: &str) -> Result<Vec<u8>, String> {
+    let header_end = match response.windows(4).position(|window| window == b"\r\n\r\n") {
+        Some(value) => value,
+        None => return Err(format!("Invalid response from {}", url)),
+    };
+
+    let header = String::from_utf8_lossy(&response[..header_end]);
+
+    let status = header
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse::<u16>().ok());
+
+    match status {
+        Some(200) => Ok(response[header_end + 4..].to_vec()),
+        Some(code) => Err(format!("Request to {} failed with status {}", url, code)),
+        None => Err(format!("Invalid response from {}", url)),
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::net::TcpListener;
+    use std::thread;
+
+    pub fn serve(routes: Vec<(&str, Vec<u8>)>) -> String {
+        let routes: HashMap<String, Vec<u8>> = routes
+            .into_iter()
+            .map(|(path, body)| (path.to_owned(), body))
+            .collect();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request = vec![];
+                let mut buffer = [0; 1024];
+
+                while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                    match stream.read(&mut buffer) {
+                        Ok(0) | Err(_) => break,
+                        Ok(count) => request.extend_from_slice(&buffer[..count]),
+                    }
+                }
+
+                let request = String::from_utf8_lossy(&request);
+                let path = request.split_whitespace().nth(1).unwrap_or("");
+
+                let response = match routes.get(path) {
+                    Some(body) => [format!("HTTP/1.0 200 OK\r\nContent-Length: {}\r\n\r\n", body.len()).into_bytes(), body.clone()].concat(),
+                    None => b"HTTP/1.0 404 Not Found\r\n\r\n".to_vec(),
+                };
+
+                let _ = stream.write_all(&response);
+            }
+        });
+
+        format!("http://{}", address)
+    }
+
+    pub fn unreachable_url() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        drop(listener);
+
+        format!("http://{}", address)
+    }
+
+    #[test]
+    fn fetches_manifest() {
+        let manifest = br#"{"images": ["a.png", "/poses/b.png"]}"#.to_vec();
+        let server = serve(vec![("/refs/manifest.json", manifest.clone())]);
+
+        assert_eq!(http_get(&format!("{}/refs/manifest.json", server)), Ok(manifest));
+    }
+
+    #[test]
+    fn downloads_binary_images() {
+        let image = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', b'\r', b'\n', 0, 255];
+        let server = serve(vec![("/refs/a.png", image.clone())]);
+
+        assert_eq!(http_get(&format!("{}/refs/a.png", server)), Ok(image));
+    }
+
+    #[test]
+    fn reports_missing_files() {
+        let server = serve(vec![]);
+        let error = http_get(&format!("{}/missing.png", server)).unwrap_err();
+
+        assert!(error.contains("404"), "{}", error);
+    }
+
+    #[test]
+    fn reports_unreachable_servers() {
+        let error = http_get(&format!("{}/manifest.json", unreachable_url())).unwrap_err();
+
+        assert!(error.starts_with("Failed to connect"), "{}", error);
+    }
+
+    #[test]
+    fn rejects_https() {
+        assert!(http_get("https://example.com/manifest.json").is_err());
+    }
+
+    #[test]
+    fn parses_urls() {
+        let url = Url::parse("http://server:8080/refs/manifest.json").unwrap();
+        assert_eq!((url.host.as_str(), url.port, url.path.as_str()), ("server", 8080, "/refs/manifest.json"));
+
+        let url = Url::parse("http://server").unwrap();
+        assert_eq!((url.host.as_str(), url.port, url.path.as_str()), ("server", 80, "/"));
+
+        assert!(Url::parse("http://:80/").is_err());
+        assert!(Url::parse("http://server:port/").is_err());
+    }
+
+    #[test]
+    fn resolves_manifest_entries() {
+        let base = "http://server:8080/refs/manifest.json";
+
+        assert_eq!(resolve_url(base, "a.png"), "http://server:8080/refs/a.png");
+        assert_eq!(resolve_url(base, "/poses/b.png"), "http://server:8080/poses/b.png");
+        assert_eq!(resolve_url(base, "http://other/c.png"), "http://other/c.png");
+    }
+}
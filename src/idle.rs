@@ -0,0 +1,41 @@
+use gtk::prelude::*;
+use gtk::{gio, glib};
+
+pub fn watch_idle_hint<R, F>(ready: R, callback: F)
+where
+    R: FnOnce(gio::DBusProxy) + 'static,
+    F: Fn(bool) + 'static,
+{
+    gio::DBusProxy::new_for_bus(
+        gio::BusType::System,
+        gio::DBusProxyFlags::NONE,
+        None::<&gio::DBusInterfaceInfo>,
+        "org.freedesktop.login1",
+        "/org/freedesktop/login1/session/auto",
+        "org.freedesktop.login1.Session",
+        None::<&gio::Cancellable>,
+        move |result| {
+            let proxy = match result {
+                Ok(value) => value,
+                Err(_) => return,
+            };
+
+            proxy.connect_local("g-properties-changed", false, move |values| {
+                let proxy = values.get(0)?.get::<gio::DBusProxy>().ok()?;
+
+                if let Some(idle) = idle_hint(&proxy) {
+                    callback(idle);
+                }
+
+                None
+            });
+
+            ready(proxy);
+        },
+    );
+}
+
+fn idle_hint(proxy: &gio::DBusProxy) -> Option<bool> {
+    let value: glib::Variant = proxy.cached_property("IdleHint")?;
+    value.get::<bool>()
+}
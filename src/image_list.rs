@@ -0,0 +1,59 @@
+use crate::storage::{read_lines, write_lines};
+
+pub const FAVOURITES_FILE: &str = "favourites";
+pub const BLOCKLIST_FILE: &str = "blocklist";
+
+pub struct ImageList {
+    name: &'static str,
+    images: Vec<String>,
+}
+
+impl ImageList {
+    pub fn load(name: &'static str) -> Self {
+        Self {
+            name,
+            images: read_lines(name),
+        }
+    }
+
+    pub fn with_images(name: &'static str, images: Vec<String>) -> Self {
+        Self {
+            name,
+            images,
+        }
+    }
+
+    pub fn save(&self) {
+        if let Err(error) = write_lines(self.name, &self.images) {
+            eprintln!("Failed to save {}: {}", self.name, error);
+        }
+    }
+
+    pub fn images(&self) -> &[String] {
+        &self.images
+    }
+
+    pub fn contains(&self, image: &str) -> bool {
+        self.images.iter().any(|value| value == image)
+    }
+
+    pub fn add(&mut self, image: &str) {
+        if !self.contains(image) {
+            self.images.push(image.to_owned());
+        }
+    }
+
+    pub fn remove(&mut self, image: &str) {
+        self.images.retain(|value| value != image);
+    }
+
+    pub fn toggle(&mut self, image: &str) -> bool {
+        if self.contains(image) {
+            self.remove(image);
+            return false;
+        }
+
+        self.add(image);
+        true
+    }
+}
@@ -0,0 +1,35 @@
+use gtk::gdk_pixbuf::{InterpType, Pixbuf};
+use std::io::Cursor;
+
+use crate::archive::{read_archive_image, split_archive_path};
+use crate::duplicates::{difference_hash, HASH_HEIGHT, HASH_WIDTH};
+
+pub fn load_pixbuf(image: &str) -> Result<Pixbuf, String> {
+    if split_archive_path(image).is_some() {
+        let bytes = read_archive_image(image)?;
+
+        return match Pixbuf::from_read(Cursor::new(bytes)) {
+            Ok(value) => Ok(value),
+            Err(_) => Err("Failed to load image".into()),
+        };
+    }
+
+    match Pixbuf::from_file(image) {
+        Ok(value) => Ok(value),
+        Err(_) => Err("Failed to load image".into()),
+    }
+}
+
+pub fn image_hash(image: &str) -> Option<u64> {
+    let width = HASH_WIDTH as i32;
+    let height = HASH_HEIGHT as i32;
+
+    let pixbuf = match split_archive_path(image) {
+        Some(_) => load_pixbuf(image).ok()?.scale_simple(width, height, InterpType::Bilinear)?,
+        None => Pixbuf::from_file_at_scale(image, width, height, false).ok()?,
+    };
+
+    let pixels = pixbuf.read_pixel_bytes()?;
+
+    difference_hash(&pixels, pixbuf.rowstride() as usize, pixbuf.n_channels() as usize)
+}
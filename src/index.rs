@@ -0,0 +1,292 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs::{create_dir_all, metadata, read_to_string, rename, write};
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::time::UNIX_EPOCH;
+
+use crate::archive::{read_archive_image, split_archive_path};
+use crate::source::read_images_in_folder;
+use crate::storage::{cache_path, hash_text};
+
+const PROGRESS_INTERVAL: usize = 100;
+
+static SAVE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+    Square,
+    Unknown,
+}
+
+impl Orientation {
+    pub fn from_dimensions(width: u32, height: u32) -> Self {
+        if width == 0 || height == 0 {
+            return Orientation::Unknown;
+        }
+
+        match width.cmp(&height) {
+            Ordering::Less => Orientation::Portrait,
+            Ordering::Greater => Orientation::Landscape,
+            Ordering::Equal => Orientation::Square,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct IndexEntry {
+    pub path: String,
+    pub modified: u64,
+    pub size: u64,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl IndexEntry {
+    fn read(path: String, modified: u64, size: u64) -> Self {
+        let (width, height) = image_dimensions(&path);
+
+        Self {
+            path,
+            modified,
+            size,
+            width,
+            height,
+        }
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        Orientation::from_dimensions(self.width, self.height)
+    }
+}
+
+pub struct ImageIndex {
+    folder: String,
+    entries: HashMap<String, IndexEntry>,
+}
+
+impl ImageIndex {
+    pub fn load(folder: &str) -> Self {
+        let mut index = Self {
+            folder: folder.to_owned(),
+            entries: HashMap::new(),
+        };
+
+        let content = match read_to_string(index_file(folder)) {
+            Ok(value) => value,
+            Err(_) => return index,
+        };
+
+        let mut lines = content.lines();
+
+        if lines.next() != Some(folder) {
+            return index;
+        }
+
+        for line in lines {
+            let values: Vec<&str> = line.splitn(5, '\t').collect();
+
+            if let [modified, size, width, height, path] = values[..] {
+                let entry = IndexEntry {
+                    path: path.to_owned(),
+                    modified: modified.parse::<u64>().unwrap_or(0),
+                    size: size.parse::<u64>().unwrap_or(0),
+                    width: width.parse::<u32>().unwrap_or(0),
+                    height: height.parse::<u32>().unwrap_or(0),
+                };
+
+                index.entries.insert(entry.path.clone(), entry);
+            }
+        }
+
+        index
+    }
+
+    pub fn save(&self) {
+        let mut lines = vec![self.folder.clone()];
+
+        for entry in self.entries() {
+            lines.push(format!("{}\t{}\t{}\t{}\t{}", entry.modified, entry.size, entry.width, entry.height, entry.path));
+        }
+
+        let file = index_file(&self.folder);
+        let temporary_file = file.with_extension(format!("{}-{}.tmp", process::id(), SAVE_COUNTER.fetch_add(1, AtomicOrdering::SeqCst)));
+
+        let result = match file.parent() {
+            Some(folder) => create_dir_all(folder),
+            None => Ok(()),
+        }
+        .and_then(|_| write(&temporary_file, lines.join("\n")))
+        .and_then(|_| rename(&temporary_file, &file));
+
+        if let Err(error) = result {
+            eprintln!("Failed to save image index: {}", error);
+        }
+    }
+
+    pub fn entries(&self) -> Vec<&IndexEntry> {
+        let mut entries: Vec<&IndexEntry> = self.entries.values().collect();
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        entries
+    }
+
+    pub fn images(&self) -> Vec<String> {
+        self.entries()
+            .into_iter()
+            .map(|entry| entry.path.clone())
+            .collect()
+    }
+
+    pub fn dimensions(&self, image: &str) -> Option<(u32, u32)> {
+        self.entries
+            .get(image)
+            .map(|entry| (entry.width, entry.height))
+    }
+
+    pub fn insert_image(&mut self, image: &str) {
+        let (modified, size) = file_stats(image);
+
+        self.entries.insert(image.to_owned(), IndexEntry::read(image.to_owned(), modified, size));
+    }
+
+    pub fn remove_image(&mut self, image: &str) {
+        self.entries.remove(image);
+    }
+
+    pub fn refresh<F: FnMut(usize, usize)>(&mut self, mut progress: F) -> Result<(), String> {
+        let images = read_images_in_folder(&self.folder)?;
+        let total = images.len();
+
+        let mut entries = HashMap::new();
+
+        for (position, image) in images.into_iter().enumerate() {
+            let (modified, size) = file_stats(&image);
+
+            let entry = match self.entries.remove(&image) {
+                Some(entry) if entry.modified == modified && entry.size == size => entry,
+                _ => IndexEntry::read(image, modified, size),
+            };
+
+            entries.insert(entry.path.clone(), entry);
+
+            if (position + 1) % PROGRESS_INTERVAL == 0 {
+                progress(position + 1, total);
+            }
+        }
+
+        progress(total, total);
+
+        self.entries = entries;
+
+        Ok(())
+    }
+
+    pub fn rescan<F: FnMut(usize, usize)>(&mut self, progress: F) -> Result<(), String> {
+        self.entries.clear();
+
+        self.refresh(progress)
+    }
+}
+
+fn index_file(folder: &str) -> PathBuf {
+    cache_path(&format!("index/{:016x}", hash_text(folder)))
+}
+
+fn modified_time(path: &str) -> u64 {
+    match metadata(path).and_then(|value| value.modified()) {
+        Ok(value) => match value.duration_since(UNIX_EPOCH) {
+            Ok(value) => value.as_secs(),
+            Err(_) => 0,
+        },
+        Err(_) => 0,
+    }
+}
+
+pub fn file_stats(image: &str) -> (u64, u64) {
+    let path = match split_archive_path(image) {
+        Some((archive, _)) => archive,
+        None => image,
+    };
+
+    let size = match metadata(path) {
+        Ok(value) => value.len(),
+        Err(_) => 0,
+    };
+
+    (modified_time(path), size)
+}
+
+pub fn image_dimensions(image: &str) -> (u32, u32) {
+    let size = match split_archive_path(image) {
+        Some(_) => match read_archive_image(image) {
+            Ok(bytes) => imagesize::blob_size(&bytes),
+            Err(_) => return (0, 0),
+        },
+        None => imagesize::size(image),
+    };
+
+    match size {
+        Ok(value) => (value.width as u32, value.height as u32),
+        Err(_) => (0, 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::temp_dir;
+    use std::fs::remove_dir_all;
+
+    fn test_folder(name: &str) -> String {
+        let folder = temp_dir().join(format!("quick-pose-index-{}-{}", name, process::id()));
+        let _ = remove_dir_all(&folder);
+        create_dir_all(&folder).unwrap();
+
+        folder.to_str().unwrap().to_owned()
+    }
+
+    #[test]
+    fn refresh_picks_up_files_overwritten_in_place() {
+        let folder = test_folder("overwrite");
+        let image = format!("{}/a.png", folder);
+        write(&image, "a").unwrap();
+
+        let mut index = ImageIndex::load(&folder);
+        index.refresh(|_, _| {}).unwrap();
+        assert_eq!(index.entries()[0].size, 1);
+
+        write(&image, "abc").unwrap();
+        index.refresh(|_, _| {}).unwrap();
+        assert_eq!(index.entries()[0].size, 3);
+
+        remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn refresh_adds_and_removes_files() {
+        let folder = test_folder("changes");
+        write(format!("{}/a.png", folder), "a").unwrap();
+
+        let mut index = ImageIndex::load(&folder);
+        index.refresh(|_, _| {}).unwrap();
+
+        write(format!("{}/b.png", folder), "b").unwrap();
+        std::fs::remove_file(format!("{}/a.png", folder)).unwrap();
+        index.refresh(|_, _| {}).unwrap();
+
+        assert_eq!(index.images(), vec![format!("{}/b.png", folder)]);
+
+        remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn orientation_follows_dimensions() {
+        assert_eq!(Orientation::from_dimensions(2, 3), Orientation::Portrait);
+        assert_eq!(Orientation::from_dimensions(3, 2), Orientation::Landscape);
+        assert_eq!(Orientation::from_dimensions(2, 2), Orientation::Square);
+        assert_eq!(Orientation::from_dimensions(0, 2), Orientation::Unknown);
+    }
+}
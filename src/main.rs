@@ -1,13 +1,38 @@
 mod app;
+mod archive;
+mod cli;
+mod compare;
 mod components;
+mod drop;
+mod duplicates;
+mod history;
+mod http;
+mod image_list;
+mod idle;
+mod image_loader;
+mod index;
 mod page;
+mod planner;
+mod progress;
+mod prompts;
+mod recent;
 mod session;
 mod settings;
+mod source;
+mod storage;
+mod tags;
+mod thumbnails;
 
 use relm4::RelmApp;
+use std::env::args;
+
+use settings::Settings;
 
 fn main() {
-    let model = app::App::new();
+    let mut settings = Settings::new();
+    cli::apply_arguments(&mut settings, args().skip(1));
+
+    let model = app::App::new(settings);
     let app = RelmApp::new(model);
     app.run();
 }
\ No newline at end of file
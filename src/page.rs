@@ -1,6 +1,9 @@
 #[derive(Clone, Copy, PartialEq)]
 pub enum Page {
     Settings,
+    Library,
+    Gallery,
+    Loading,
     Session,
     Complete,
     Error,
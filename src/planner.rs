@@ -0,0 +1,235 @@
+use std::time::Duration;
+
+const DEFAULT_POSE_SECONDS: u64 = 60;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PoseCurve {
+    Constant,
+    ShortToLong,
+    LongToShort,
+    WarmUp,
+}
+
+impl PoseCurve {
+    pub fn id(&self) -> &'static str {
+        match self {
+            PoseCurve::Constant => "constant",
+            PoseCurve::ShortToLong => "short-to-long",
+            PoseCurve::LongToShort => "long-to-short",
+            PoseCurve::WarmUp => "warm-up",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "constant" => Some(PoseCurve::Constant),
+            "short-to-long" => Some(PoseCurve::ShortToLong),
+            "long-to-short" => Some(PoseCurve::LongToShort),
+            "warm-up" => Some(PoseCurve::WarmUp),
+            _ => None,
+        }
+    }
+
+    fn tiers(&self, pose: Duration) -> Vec<(u64, u64)> {
+        match self {
+            PoseCurve::Constant => vec![(pose.as_secs(), 100)],
+            PoseCurve::ShortToLong => vec![(30, 20), (60, 20), (120, 30), (300, 30)],
+            PoseCurve::LongToShort => vec![(300, 30), (120, 30), (60, 20), (30, 20)],
+            PoseCurve::WarmUp => vec![(30, 15), (60, 15), (300, 50), (60, 20)],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Segment {
+    pub duration: Duration,
+    pub count: usize,
+}
+
+pub fn plan_session(budget: Duration, curve: PoseCurve, pose: Option<Duration>, pause: Option<Duration>) -> Vec<Segment> {
+    let pose = match pose {
+        Some(value) if !value.is_zero() => value,
+        _ => Duration::from_secs(DEFAULT_POSE_SECONDS),
+    };
+
+    let pause = pause.unwrap_or_default().as_secs();
+
+    let budget = budget.as_secs() + pause;
+
+    let tiers = curve.tiers(pose);
+    let total_weight: u64 = tiers.iter().map(|(_, weight)| weight).sum();
+
+    let mut segments = vec![];
+    let mut carry = 0;
+    let mut allotted = 0;
+    let mut cumulative_weight = 0;
+
+    for (seconds, weight) in &tiers {
+        cumulative_weight += weight;
+
+        let cost = seconds + pause;
+        let end = budget * cumulative_weight / total_weight;
+        let share = end - allotted + carry;
+        allotted = end;
+
+        segments.push(Segment {
+            duration: Duration::from_secs(*seconds),
+            count: (share / cost) as usize,
+        });
+
+        carry = share % cost;
+    }
+
+    let shortest = segments
+        .iter_mut()
+        .min_by_key(|segment| segment.duration);
+
+    if let Some(segment) = shortest {
+        let cost = segment.duration.as_secs() + pause;
+        segment.count += (carry / cost) as usize;
+    }
+
+    segments
+        .into_iter()
+        .filter(|segment| segment.count > 0)
+        .collect()
+}
+
+pub fn segment_durations(segments: &[Segment]) -> Vec<Duration> {
+    segments
+        .iter()
+        .flat_map(|segment| vec![segment.duration; segment.count])
+        .collect()
+}
+
+pub fn fit_durations(durations: &[Duration], images: usize, budget: Duration, pause: Duration) -> Vec<Duration> {
+    if images >= durations.len() {
+        return durations.to_vec();
+    }
+
+    if images == 0 {
+        return vec![];
+    }
+
+    let picked: Vec<u64> = (0..images)
+        .map(|index| durations[index * durations.len() / images].as_secs())
+        .collect();
+
+    let planned: u64 = picked.iter().sum();
+    let available = budget.as_secs().saturating_sub(pause.as_secs() * (images as u64 - 1));
+
+    picked
+        .into_iter()
+        .map(|seconds| Duration::from_secs(u64::max(1, seconds * available / u64::max(planned, 1))))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minutes(value: u64) -> Duration {
+        Duration::from_secs(value * 60)
+    }
+
+    fn session_length(durations: &[Duration], pause: Duration) -> Duration {
+        let pauses = pause * durations.len().saturating_sub(1) as u32;
+        durations.iter().sum::<Duration>() + pauses
+    }
+
+    #[test]
+    fn plans_fit_the_budget() {
+        for curve in [PoseCurve::Constant, PoseCurve::ShortToLong, PoseCurve::LongToShort, PoseCurve::WarmUp] {
+            for budget in [5, 15, 30, 60, 90] {
+                for pause in [0, 5, 30] {
+                    let pause = Duration::from_secs(pause);
+                    let durations = segment_durations(&plan_session(minutes(budget), curve, None, Some(pause)));
+                    let length = session_length(&durations, pause);
+                    let shortest = durations.iter().min().copied().unwrap_or_default();
+
+                    assert!(length <= minutes(budget), "{:?} {} {:?}: {:?}", curve, budget, pause, length);
+                    assert!(minutes(budget) - length < shortest + pause, "{:?} {} {:?}: {:?}", curve, budget, pause, length);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn short_to_long_never_gets_shorter() {
+        let durations = segment_durations(&plan_session(minutes(60), PoseCurve::ShortToLong, None, None));
+
+        assert!(durations.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(durations.first(), Some(&Duration::from_secs(30)));
+        assert_eq!(durations.last(), Some(&Duration::from_secs(300)));
+    }
+
+    #[test]
+    fn long_to_short_never_gets_longer() {
+        let durations = segment_durations(&plan_session(minutes(60), PoseCurve::LongToShort, None, None));
+
+        assert!(durations.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert_eq!(durations.first(), Some(&Duration::from_secs(300)));
+    }
+
+    #[test]
+    fn warm_up_ends_with_short_poses_after_the_long_ones() {
+        let segments = plan_session(minutes(60), PoseCurve::WarmUp, None, None);
+        let seconds: Vec<u64> = segments.iter().map(|segment| segment.duration.as_secs()).collect();
+
+        assert_eq!(seconds, vec![30, 60, 300, 60]);
+    }
+
+    #[test]
+    fn constant_curve_uses_the_pose_duration() {
+        let segments = plan_session(minutes(10), PoseCurve::Constant, Some(Duration::from_secs(90)), None);
+
+        assert_eq!(segments, vec![Segment { duration: Duration::from_secs(90), count: 6 }]);
+    }
+
+    #[test]
+    fn missing_pose_duration_defaults_to_a_minute() {
+        let segments = plan_session(minutes(5), PoseCurve::Constant, None, None);
+
+        assert_eq!(segments, vec![Segment { duration: minutes(1), count: 5 }]);
+    }
+
+    #[test]
+    fn leftover_time_goes_to_the_shortest_pose() {
+        let segments = plan_session(minutes(10), PoseCurve::ShortToLong, None, None);
+
+        assert_eq!(segments.first().map(|segment| segment.duration), Some(Duration::from_secs(30)));
+        assert_eq!(session_length(&segment_durations(&segments), Duration::ZERO), minutes(10));
+    }
+
+    #[test]
+    fn tiny_budgets_give_empty_plans() {
+        assert!(plan_session(Duration::from_secs(10), PoseCurve::ShortToLong, None, None).is_empty());
+        assert!(plan_session(Duration::ZERO, PoseCurve::Constant, None, None).is_empty());
+    }
+
+    #[test]
+    fn fitting_keeps_plans_that_have_enough_images() {
+        let durations = segment_durations(&plan_session(minutes(30), PoseCurve::ShortToLong, None, None));
+
+        assert_eq!(fit_durations(&durations, durations.len() + 5, minutes(30), Duration::ZERO), durations);
+    }
+
+    #[test]
+    fn fitting_stretches_plans_over_fewer_images() {
+        let pause = Duration::from_secs(10);
+        let durations = segment_durations(&plan_session(minutes(30), PoseCurve::ShortToLong, None, Some(pause)));
+        let fitted = fit_durations(&durations, 5, minutes(30), pause);
+
+        assert_eq!(fitted.len(), 5);
+        assert!(fitted.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(session_length(&fitted, pause) <= minutes(30));
+        assert!(minutes(30) - session_length(&fitted, pause) < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn fitting_to_no_images_gives_an_empty_plan() {
+        let durations = segment_durations(&plan_session(minutes(30), PoseCurve::ShortToLong, None, None));
+
+        assert!(fit_durations(&durations, 0, minutes(30), Duration::ZERO).is_empty());
+    }
+}
@@ -0,0 +1,31 @@
+use crate::storage::{read_lines, write_lines};
+
+const PROGRESS_FILE: &str = "progress";
+
+pub fn load_progress(source: &str) -> usize {
+    for line in read_lines(PROGRESS_FILE) {
+        if let Some((index, path)) = line.split_once('\t') {
+            if path == source {
+                return index.parse::<usize>().unwrap_or(0);
+            }
+        }
+    }
+
+    0
+}
+
+pub fn save_progress(source: &str, index: usize) {
+    let mut lines: Vec<String> = read_lines(PROGRESS_FILE)
+        .into_iter()
+        .filter(|line| match line.split_once('\t') {
+            Some((_, path)) => path != source,
+            None => false,
+        })
+        .collect();
+
+    lines.push(format!("{}\t{}", index, source));
+
+    if let Err(error) = write_lines(PROGRESS_FILE, &lines) {
+        eprintln!("Failed to save progress: {}", error);
+    }
+}
@@ -0,0 +1,211 @@
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::storage::{read_lines, write_lines};
+
+const INTERMISSION_PROMPTS_FILE: &str = "intermission_prompts";
+
+const DEFAULT_INTERMISSION_PROMPTS: [&str; 6] = [
+    "Draw the line of action",
+    "Shake out your hand",
+    "Roll your shoulders",
+    "Look away from the screen",
+    "Check the proportions of your last drawing",
+    "Sharpen your pencil",
+];
+
+pub fn load_intermission_prompts() -> Vec<String> {
+    let prompts = read_lines(INTERMISSION_PROMPTS_FILE);
+
+    if prompts.is_empty() {
+        return DEFAULT_INTERMISSION_PROMPTS
+            .iter()
+            .map(|prompt| prompt.to_string())
+            .collect();
+    }
+
+    prompts
+}
+
+pub fn save_intermission_prompts(text: &str) {
+    let prompts: Vec<String> = text
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_owned())
+        .collect();
+
+    if let Err(error) = write_lines(INTERMISSION_PROMPTS_FILE, &prompts) {
+        eprintln!("Failed to save intermission prompts: {}", error);
+    }
+}
+
+const PROMPT_DECK_FILE: &str = "prompt_deck";
+
+const DEFAULT_PROMPT_DECK: [(u32, &str); 10] = [
+    (3, "Line of action only"),
+    (2, "Draw with your non-dominant hand"),
+    (2, "Use only 5 lines"),
+    (2, "Draw the negative space"),
+    (3, "Simple shapes only, no details"),
+    (2, "Don't lift your pencil"),
+    (1, "Don't look at the paper"),
+    (2, "Focus on the hands"),
+    (2, "Exaggerate the pose"),
+    (1, "Draw it from the opposite side"),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptCard {
+    pub text: String,
+    pub weight: u32,
+}
+
+pub fn load_prompt_deck() -> Vec<PromptCard> {
+    let lines = read_lines(PROMPT_DECK_FILE);
+
+    if lines.is_empty() {
+        return DEFAULT_PROMPT_DECK
+            .iter()
+            .map(|(weight, text)| PromptCard {
+                text: text.to_string(),
+                weight: *weight,
+            })
+            .collect();
+    }
+
+    lines
+        .iter()
+        .filter_map(|line| parse_prompt_card(line))
+        .collect()
+}
+
+pub fn save_prompt_deck(text: &str) {
+    let lines: Vec<String> = text
+        .lines()
+        .filter_map(parse_prompt_card)
+        .map(|card| format_prompt_card(&card))
+        .collect();
+
+    if let Err(error) = write_lines(PROMPT_DECK_FILE, &lines) {
+        eprintln!("Failed to save prompt deck: {}", error);
+    }
+}
+
+pub fn format_prompt_card(card: &PromptCard) -> String {
+    format!("{} {}", card.weight, card.text)
+}
+
+pub fn parse_prompt_card(line: &str) -> Option<PromptCard> {
+    let line = line.trim();
+
+    if line.is_empty() {
+        return None;
+    }
+
+    let card = match line.split_once(' ') {
+        Some((weight, text)) => match weight.parse::<u32>() {
+            Ok(weight) => PromptCard {
+                text: text.trim().to_owned(),
+                weight,
+            },
+            Err(_) => PromptCard {
+                text: line.to_owned(),
+                weight: 1,
+            },
+        },
+        None => PromptCard {
+            text: line.to_owned(),
+            weight: 1,
+        },
+    };
+
+    match card.text.is_empty() {
+        true => None,
+        false => Some(card),
+    }
+}
+
+pub fn draw_prompt(deck: &[PromptCard], seed: u64, stream: u64) -> Option<String> {
+    let weights = WeightedIndex::new(deck.iter().map(|card| card.weight)).ok()?;
+
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    rng.set_stream(stream);
+
+    Some(deck[weights.sample(&mut rng)].text.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(weight: u32, text: &str) -> PromptCard {
+        PromptCard {
+            text: text.into(),
+            weight,
+        }
+    }
+
+    #[test]
+    fn parses_weighted_cards() {
+        assert_eq!(parse_prompt_card("3 Line of action only"), Some(card(3, "Line of action only")));
+        assert_eq!(parse_prompt_card("  2   Use only 5 lines  "), Some(card(2, "Use only 5 lines")));
+    }
+
+    #[test]
+    fn cards_without_weight_default_to_one() {
+        assert_eq!(parse_prompt_card("Gesture only"), Some(card(1, "Gesture only")));
+        assert_eq!(parse_prompt_card("Simple"), Some(card(1, "Simple")));
+        assert_eq!(parse_prompt_card("5lines only"), Some(card(1, "5lines only")));
+    }
+
+    #[test]
+    fn skips_empty_cards() {
+        assert_eq!(parse_prompt_card(""), None);
+        assert_eq!(parse_prompt_card("   "), None);
+    }
+
+    #[test]
+    fn formatted_cards_parse_back() {
+        for (weight, text) in DEFAULT_PROMPT_DECK {
+            let card = card(weight, text);
+            assert_eq!(parse_prompt_card(&format_prompt_card(&card)), Some(card));
+        }
+    }
+
+    #[test]
+    fn drawing_is_deterministic() {
+        let deck: Vec<PromptCard> = (1..=10).map(|index| card(1, &format!("Prompt {}", index))).collect();
+
+        for stream in 0..20 {
+            assert_eq!(draw_prompt(&deck, 42, stream), draw_prompt(&deck, 42, stream));
+        }
+    }
+
+    #[test]
+    fn drawing_skips_zero_weight_cards() {
+        let deck = vec![card(0, "Never"), card(1, "Always")];
+
+        for stream in 0..20 {
+            assert_eq!(draw_prompt(&deck, 7, stream), Some("Always".into()));
+        }
+    }
+
+    #[test]
+    fn drawing_follows_weights() {
+        let deck = vec![card(9, "Common"), card(1, "Rare")];
+        let common = (0..1000)
+            .filter(|stream| draw_prompt(&deck, 3, *stream).as_deref() == Some("Common"))
+            .count();
+
+        assert!(common > 800);
+        assert!(common < 980);
+    }
+
+    #[test]
+    fn empty_decks_draw_nothing() {
+        assert_eq!(draw_prompt(&[], 1, 0), None);
+        assert_eq!(draw_prompt(&[card(0, "Never")], 1, 0), None);
+    }
+}
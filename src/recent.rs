@@ -0,0 +1,111 @@
+use std::path::Path;
+
+use crate::storage::{read_lines, write_lines};
+
+const RECENT_FOLDERS_FILE: &str = "recent_folders";
+pub const MAX_RECENT_FOLDERS: usize = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecentFolder {
+    pub path: String,
+    pub pinned: bool,
+}
+
+impl RecentFolder {
+    pub fn exists(&self) -> bool {
+        Path::new(&self.path).exists()
+    }
+}
+
+pub struct RecentFolders {
+    folders: Vec<RecentFolder>,
+}
+
+impl RecentFolders {
+    pub fn load() -> Self {
+        let folders = read_lines(RECENT_FOLDERS_FILE)
+            .iter()
+            .filter_map(|line| parse_line(line))
+            .collect();
+
+        Self::with_folders(folders)
+    }
+
+    pub fn with_folders(folders: Vec<RecentFolder>) -> Self {
+        let mut recent = Self { folders };
+        recent.sort();
+        recent
+    }
+
+    pub fn save(&self) {
+        let lines: Vec<String> = self.folders
+            .iter()
+            .map(|folder| format!("{}\t{}", folder.pinned as u8, folder.path))
+            .collect();
+
+        if let Err(error) = write_lines(RECENT_FOLDERS_FILE, &lines) {
+            eprintln!("Failed to save recent folders: {}", error);
+        }
+    }
+
+    pub fn folders(&self) -> &[RecentFolder] {
+        &self.folders
+    }
+
+    pub fn add(&mut self, path: &str) {
+        if path.is_empty() {
+            return;
+        }
+
+        let pinned = self.folders
+            .iter()
+            .any(|folder| folder.path == path && folder.pinned);
+
+        self.folders.retain(|folder| folder.path != path);
+        self.folders.insert(0, RecentFolder {
+            path: path.to_owned(),
+            pinned,
+        });
+
+        self.sort();
+    }
+
+    pub fn remove(&mut self, path: &str) {
+        self.folders.retain(|folder| folder.path != path);
+    }
+
+    pub fn toggle_pin(&mut self, path: &str) -> bool {
+        let mut pinned = false;
+
+        for folder in self.folders.iter_mut().filter(|folder| folder.path == path) {
+            folder.pinned = !folder.pinned;
+            pinned = folder.pinned;
+        }
+
+        self.sort();
+        pinned
+    }
+
+    fn sort(&mut self) {
+        self.folders.sort_by_key(|folder| !folder.pinned);
+
+        let mut unpinned = 0;
+        self.folders.retain(|folder| {
+            if folder.pinned {
+                return true;
+            }
+
+            unpinned += 1;
+            unpinned <= MAX_RECENT_FOLDERS
+        });
+    }
+}
+
+fn parse_line(line: &str) -> Option<RecentFolder> {
+    let (pinned, path) = line.split_once('\t')?;
+
+    Some(RecentFolder {
+        path: path.to_owned(),
+        pinned: pinned == "1",
+    })
+}
@@ -1,9 +1,24 @@
-use std::fs::read_dir;
+use std::fs::metadata;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::time::SystemTime;
 use core::fmt::Debug;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::{thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
-use super::settings::Settings;
+use super::archive::image_exists;
+use super::settings::{SelectionMode, Settings};
+use super::image_list::{ImageList, BLOCKLIST_FILE};
+use super::index::{file_stats, image_dimensions, ImageIndex};
+use super::http::is_remote;
+use super::duplicates::{DuplicateClusters, HashCache, DUPLICATE_THRESHOLD};
+use super::image_loader::image_hash;
+use super::progress::{load_progress, save_progress};
+use super::source::{create_source, ImageSource};
+use super::tags::{TagQuery, TagStore};
+use super::prompts::{draw_prompt, load_prompt_deck, PromptCard};
+use super::storage::hash_text;
 
 #[derive(Debug, Clone)]
 pub struct Session {
@@ -11,6 +26,25 @@ pub struct Session {
     pub current_image: usize,
     pub session_time: usize,
     pub pause_time: usize,
+    pub memory_time: usize,
+    pub reveal_time: usize,
+    pub seed: u64,
+    pub source: String,
+    pub offset: Option<usize>,
+    pub duplicates: Vec<Vec<String>>,
+    pub duplicates_scanned: usize,
+    pub pool: Vec<String>,
+    pub watch_folder: Option<String>,
+    pub settings: Settings,
+    pub endless: bool,
+    pub durations: Vec<usize>,
+    pub time_spent: Vec<usize>,
+    pub extensions: Vec<usize>,
+    pub deck: Vec<PromptCard>,
+    pub drawings: HashMap<String, String>,
+    pub warning: Option<String>,
+    cycle: u64,
+    dropped: usize,
 }
 
 impl Session {
@@ -20,13 +54,115 @@ impl Session {
             current_image: 0,
             session_time: 0,
             pause_time: 0,
+            memory_time: 0,
+            reveal_time: 0,
+            seed: 0,
+            source: "".into(),
+            offset: None,
+            duplicates: vec![],
+            duplicates_scanned: 0,
+            pool: vec![],
+            watch_folder: None,
+            settings: Settings::new(),
+            endless: false,
+            durations: vec![],
+            time_spent: vec![],
+            extensions: vec![],
+            deck: vec![],
+            drawings: HashMap::new(),
+            warning: None,
+            cycle: 0,
+            dropped: 0,
         }
     }
 
-    pub fn from(settings: &Settings) -> Self {
-        let images = read_images_in_folder(&settings.folder);
-        let images = select_images(images, settings.images_number);
+    pub fn from(settings: &Settings, progress: &dyn Fn(usize, usize)) -> Result<Self, String> {
+        let source = create_source(settings);
 
+        let seed = match settings.seed {
+            Some(value) => value,
+            None => generate_seed(),
+        };
+
+        let offset = match settings.selection_mode {
+            SelectionMode::Continue => Some(load_progress(&source.name())),
+            _ => None,
+        };
+
+        let images = read_pool(settings, source.as_ref())?;
+        let images = order_images(images, settings.selection_mode, Some(seed));
+
+        let (images, pool, offset, duplicates, scanned) = match settings.skip_duplicates {
+            true => {
+                let selection = select_unique_images(images, settings.session_images(), offset, &source.name());
+                (selection.images, selection.pool, selection.offset, selection.duplicates, selection.scanned)
+            },
+            false => {
+                let (images, pool, offset) = select_images(images, settings.session_images(), offset);
+                (images, pool, offset, vec![], 0)
+            },
+        };
+        let prepared = source.prepare(images, progress)?;
+
+        let warning = prepared.warning();
+
+        let mut session = Self::with_images(settings, prepared.images, seed);
+        session.source = source.name();
+        session.warning = warning;
+        session.offset = offset;
+        session.duplicates = duplicates;
+        session.duplicates_scanned = scanned;
+
+        if !source.remote() {
+            session.pool = pool;
+        }
+
+        if settings.watch_folder {
+            session.watch_folder = source.folder();
+        }
+
+        Ok(session)
+    }
+
+    pub fn from_selection(settings: &Settings, images: Vec<String>, progress: &dyn Fn(usize, usize)) -> Result<Self, String> {
+        let source = create_source(settings);
+
+        let seed = match settings.seed {
+            Some(value) => value,
+            None => generate_seed(),
+        };
+
+        let mode = match settings.selection_mode {
+            SelectionMode::Continue => SelectionMode::Natural,
+            value => value,
+        };
+
+        let images = order_images(images, mode, Some(seed));
+        let prepared = source.prepare(images, progress)?;
+
+        let warning = prepared.warning();
+
+        let mut session = Self::with_images(settings, prepared.images, seed);
+        session.source = source.name();
+        session.warning = warning;
+
+        Ok(session)
+    }
+
+    pub fn pool(settings: &Settings) -> Result<Vec<String>, String> {
+        let source = create_source(settings);
+
+        read_pool(settings, source.as_ref())
+    }
+
+    pub fn count_pool(settings: &Settings) -> Result<usize, String> {
+        let source = create_source(settings);
+        let images = filter_pool(settings, source.preview_images()?, &source.name())?;
+
+        Ok(images.len())
+    }
+
+    fn with_images(settings: &Settings, images: Vec<String>, seed: u64) -> Self {
         let duration = match settings.duration {
             Some(value) => value.as_secs() as usize,
             None => 0,
@@ -37,14 +173,58 @@ impl Session {
             None => 0,
         };
 
+        let memory = match settings.memory_time {
+            Some(value) => value.as_secs() as usize,
+            None => 0,
+        };
+
+        let deck = match settings.exercise_prompts {
+            true => load_prompt_deck(),
+            false => vec![],
+        };
+
+        let durations = match settings.fitted_durations(images.len()) {
+            Some(value) => value.iter().map(|duration| duration.as_secs() as usize).collect(),
+            None => vec![],
+        };
+
         Self {
             current_image: 0,
             images,
             session_time: duration,
             pause_time: pause,
+            memory_time: memory,
+            reveal_time: settings.reveal_time.as_secs() as usize,
+            seed,
+            source: "".into(),
+            offset: None,
+            duplicates: vec![],
+            duplicates_scanned: 0,
+            pool: vec![],
+            watch_folder: None,
+            settings: settings.clone(),
+            endless: settings.endless,
+            durations,
+            time_spent: vec![],
+            extensions: vec![],
+            deck,
+            drawings: HashMap::new(),
+            warning: None,
+            cycle: 0,
+            dropped: 0,
+        }
+    }
+
+    pub fn save_progress(&self) {
+        if let Some(progress) = self.progress() {
+            save_progress(&self.source, progress);
         }
     }
 
+    fn progress(&self) -> Option<usize> {
+        Some(self.offset? + self.current_image + self.dropped + 1)
+    }
+
     pub fn current_image(&self) -> Option<String> {
         match self.images.get(self.current_image) {
             Some(image) => Some(image.to_owned()),
@@ -66,7 +246,7 @@ impl Session {
     }
 
     pub fn next_image(&mut self) -> Option<String> {
-        if self.current_image + 1 == self.images.len() {
+        if self.current_image + 1 >= self.images.len() && !self.draw_image() {
             return None;
         }
 
@@ -77,42 +257,658 @@ impl Session {
         
         None
     }
-}
 
-fn read_images_in_folder(folder: &str) -> Vec<String> {
-    let mut images = vec![];
+    pub fn image_time(&self) -> usize {
+        match self.durations.get(self.current_image) {
+            Some(value) => *value,
+            None => self.session_time,
+        }
+    }
+
+    pub fn record_time(&mut self, seconds: usize) {
+        add_seconds(&mut self.time_spent, self.current_image, seconds);
+    }
 
-    for entry in read_dir(folder).expect("Failed to read folder") {
-        if let Ok(entry) = entry {
-            if entry.path().is_dir() {
-                continue;
+    pub fn record_extension(&mut self, seconds: usize) {
+        add_seconds(&mut self.extensions, self.current_image, seconds);
+    }
+
+    pub fn image_extension(&self, index: usize) -> usize {
+        self.extensions.get(index).copied().unwrap_or(0)
+    }
+
+    pub fn total_time_spent(&self) -> usize {
+        self.time_spent.iter().sum()
+    }
+
+    pub fn attach_drawing(&mut self, image: String, drawing: String) {
+        self.drawings.insert(image, drawing);
+    }
+
+    pub fn current_drawing(&self) -> Option<String> {
+        let image = self.images.get(self.current_image)?;
+        self.drawings.get(image).cloned()
+    }
+
+    pub fn current_prompt(&self) -> Option<String> {
+        let image = self.images.get(self.current_image)?;
+        draw_prompt(&self.deck, self.seed, hash_text(image))
+    }
+
+    pub fn upcoming_image(&self) -> Option<String> {
+        match self.images.get(self.current_image + 1) {
+            Some(image) => Some(image.to_owned()),
+            None if self.endless => self.pool.first().cloned(),
+            None => None,
+        }
+    }
+
+    pub fn images_done(&self) -> usize {
+        usize::min(self.current_image + 1, self.images.len())
+    }
+
+    pub fn finish(&mut self) {
+        if self.endless {
+            self.images.truncate(self.images_done());
+        }
+    }
+
+    fn draw_image(&mut self) -> bool {
+        if !self.endless {
+            return false;
+        }
+
+        if self.pool.is_empty() {
+            self.cycle += 1;
+
+            let mut images = self.images.clone();
+            images.sort();
+            images.dedup();
+
+            let mut images = shuffle_images(images, Some(self.seed.wrapping_add(self.cycle)));
+
+            if images.len() > 1 && images.first() == self.images.last() {
+                images.rotate_left(1);
             }
 
-            if let Some(name) = entry.file_name().to_str() {
-                let name = format!("{}/{}", folder, name);
-                images.push(name);
+            self.pool = images;
+        }
+
+        if self.pool.is_empty() {
+            return false;
+        }
+
+        self.images.push(self.pool.remove(0));
+        true
+    }
+
+    pub fn current_existing_image(&mut self) -> Option<String> {
+        while let Some(image) = self.current_image() {
+            if image_exists(&image) {
+                return Some(image);
+            }
+
+            self.drop_current_image();
+        }
+
+        None
+    }
+
+    pub fn previous_existing_image(&mut self) -> Option<String> {
+        while let Some(image) = self.previous_image() {
+            if image_exists(&image) {
+                return Some(image);
+            }
+
+            self.remove_entry(self.current_image);
+        }
+
+        None
+    }
+
+    pub fn next_existing_image(&mut self) -> Option<String> {
+        while let Some(image) = self.next_image() {
+            if image_exists(&image) {
+                return Some(image);
+            }
+
+            self.drop_current_image();
+            self.current_image -= 1;
+        }
+
+        None
+    }
+
+    fn drop_current_image(&mut self) {
+        if self.current_image >= self.images.len() {
+            return;
+        }
+
+        self.remove_entry(self.current_image);
+
+        if !self.pool.is_empty() {
+            self.images.push(self.pool.remove(0));
+        }
+    }
+
+    fn remove_entry(&mut self, position: usize) {
+        self.images.remove(position);
+        self.dropped += 1;
+
+        for values in [&mut self.time_spent, &mut self.extensions] {
+            if position < values.len() {
+                values.remove(position);
+            }
+        }
+    }
+
+    pub fn accepts_image(&self, image: &str) -> bool {
+        if self.images.iter().any(|value| value == image) || self.pool.iter().any(|value| value == image) {
+            return false;
+        }
+
+        let query = TagQuery::parse(&self.settings.tag_query).unwrap_or_default();
+        let images = filter_images(vec![image.to_owned()], query);
+        let images = filter_dimensions(images, &self.settings, &self.source);
+
+        !images.is_empty()
+    }
+
+    pub fn add_image(&mut self, image: String) {
+        if !self.accepts_image(&image) {
+            return;
+        }
+
+        let position = match self.pool.is_empty() {
+            true => 0,
+            false => thread_rng().gen_range(0..=self.pool.len()),
+        };
+
+        self.pool.insert(position, image);
+    }
+
+    pub fn queue_images(&mut self, images: Vec<String>) {
+        let position = usize::min(self.current_image + 1, self.images.len());
+
+        for image in &images {
+            self.pool.retain(|value| value != image);
+        }
+
+        let upcoming: Vec<String> = self.images
+            .split_off(position)
+            .into_iter()
+            .filter(|value| !images.contains(value))
+            .collect();
+
+        self.images.extend(images);
+        self.images.extend(upcoming);
+    }
+
+    pub fn remove_image(&mut self, image: &str) {
+        self.pool.retain(|value| value != image);
+
+        let position = self.images
+            .iter()
+            .position(|value| value == image);
+
+        if let Some(position) = position {
+            if position > self.current_image {
+                self.images.remove(position);
+
+                if !self.pool.is_empty() {
+                    self.images.push(self.pool.remove(0));
+                }
             }
         }
     }
-    
+}
+
+fn add_seconds(values: &mut Vec<usize>, index: usize, seconds: usize) {
+    if values.len() <= index {
+        values.resize(index + 1, 0);
+    }
+
+    values[index] += seconds;
+}
+
+fn read_pool(settings: &Settings, source: &dyn ImageSource) -> Result<Vec<String>, String> {
+    filter_pool(settings, source.images()?, &source.name())
+}
+
+fn filter_pool(settings: &Settings, images: Vec<String>, source: &str) -> Result<Vec<String>, String> {
+    let query = TagQuery::parse(&settings.tag_query)?;
+
+    let images = filter_images(images, query);
+    let images = filter_dimensions(images, settings, source);
+
+    Ok(images)
+}
+
+fn filter_images(images: Vec<String>, query: Option<TagQuery>) -> Vec<String> {
+    let blocklist = ImageList::load(BLOCKLIST_FILE);
+    let blocked: HashSet<&str> = blocklist
+        .images()
+        .iter()
+        .map(|image| image.as_str())
+        .collect();
+
+    let images = images
+        .into_iter()
+        .filter(|image| !blocked.contains(image.as_str()))
+        .collect();
+
+    let query = match query {
+        Some(value) => value,
+        None => return images,
+    };
+
+    let tags = TagStore::load();
+
+    images
+        .into_iter()
+        .filter(|image| query.matches(&tags.image_tags(image)))
+        .collect()
+}
+
+fn filter_dimensions(images: Vec<String>, settings: &Settings, source: &str) -> Vec<String> {
+    if !settings.filters_dimensions() {
+        return images;
+    }
+
+    let index = ImageIndex::load(source);
+
     images
+        .into_iter()
+        .filter(|image| {
+            if is_remote(image) {
+                return true;
+            }
+
+            let (width, height) = indexed_dimensions(&index, image);
+            settings.matches_dimensions(width, height)
+        })
+        .collect()
+}
+
+fn indexed_dimensions(index: &ImageIndex, image: &str) -> (u32, u32) {
+    match index.dimensions(image) {
+        Some(value) => value,
+        None => image_dimensions(image),
+    }
+}
+
+struct UniqueSelection {
+    images: Vec<String>,
+    pool: Vec<String>,
+    offset: Option<usize>,
+    duplicates: Vec<Vec<String>>,
+    scanned: usize,
 }
 
-fn select_images(images: Vec<String>, number_of_images_to_select: usize) -> Vec<String> {
-    let images = shuffle_images(images);
+fn select_unique_images(images: Vec<String>, number_of_images_to_select: usize, offset: Option<usize>, source: &str) -> UniqueSelection {
+    let index = ImageIndex::load(source);
+    let mut cache = HashCache::load();
 
-    if number_of_images_to_select > images.len() {
-        return images[..images.len()].to_vec();
+    let selection = pick_unique_images(
+        images,
+        number_of_images_to_select,
+        offset,
+        |image| {
+            let (modified, _) = file_stats(image);
+            cache.hash(image, modified, image_hash)
+        },
+        |image| image_area(&index, image),
+    );
+
+    cache.save();
+
+    selection
+}
+
+fn pick_unique_images<H, A>(images: Vec<String>, number_of_images_to_select: usize, offset: Option<usize>, mut hash: H, area: A) -> UniqueSelection
+where
+    H: FnMut(&str) -> Option<u64>,
+    A: Fn(&str) -> u64,
+{
+    let total = images.len();
+    let (mut candidates, wrapped, offset) = select_images(images, total, offset);
+    candidates.extend(wrapped);
+
+    let mut clusters = DuplicateClusters::new(DUPLICATE_THRESHOLD);
+    let mut kept: HashMap<usize, usize> = HashMap::new();
+
+    let mut selected: Vec<String> = vec![];
+    let mut remaining = vec![];
+    let mut scanned = 0;
+
+    for image in candidates {
+        if selected.len() >= number_of_images_to_select {
+            remaining.push(image);
+            continue;
+        }
+
+        scanned += 1;
+
+        let hash = match hash(&image) {
+            Some(value) => value,
+            None => {
+                selected.push(image);
+                continue;
+            },
+        };
+
+        match clusters.find(hash) {
+            Some(cluster) => {
+                clusters.join(cluster, &image);
+
+                if let Some(position) = kept.get(&cluster).copied() {
+                    if area(&image) > area(&selected[position]) {
+                        selected[position] = image;
+                    }
+                }
+            },
+            None => {
+                kept.insert(clusters.add(&image, hash), selected.len());
+                selected.push(image);
+            },
+        }
     }
 
-    images[..number_of_images_to_select].to_vec()
+    let skipped = scanned - selected.len();
+    let offset = offset.map(|value| value + skipped);
+
+    UniqueSelection {
+        images: selected,
+        pool: remaining,
+        offset,
+        duplicates: clusters.duplicates(),
+        scanned,
+    }
 }
 
-fn shuffle_images(images: Vec<String>) -> Vec<String> {
-    let mut rng = thread_rng();
+fn image_area(index: &ImageIndex, image: &str) -> u64 {
+    let (width, height) = indexed_dimensions(index, image);
+    width as u64 * height as u64
+}
+
+pub fn generate_seed() -> u64 {
+    thread_rng().gen_range(0..1_000_000)
+}
+
+fn order_images(images: Vec<String>, mode: SelectionMode, seed: Option<u64>) -> Vec<String> {
+    let mut images = images;
+    images.sort();
+
+    match mode {
+        SelectionMode::Random => shuffle_images(images, seed),
+        SelectionMode::Alphabetical => images,
+        SelectionMode::Natural | SelectionMode::Continue => {
+            images.sort_by(|a, b| natural_compare(a, b));
+            images
+        },
+        SelectionMode::Modified => {
+            images.sort_by_key(|image| modified_time(image));
+            images
+        },
+    }
+}
+
+fn select_images(images: Vec<String>, number_of_images_to_select: usize, offset: Option<usize>) -> (Vec<String>, Vec<String>, Option<usize>) {
+    let offset = match offset {
+        Some(value) if value < images.len() => Some(value),
+        Some(_) => Some(0),
+        None => None,
+    };
+
+    let start = offset.unwrap_or(0);
+    let end = usize::min(start + number_of_images_to_select, images.len());
+
+    let mut remaining = images[end..].to_vec();
+    remaining.extend_from_slice(&images[..start]);
+
+    (images[start..end].to_vec(), remaining, offset)
+}
+
+fn shuffle_images(images: Vec<String>, seed: Option<u64>) -> Vec<String> {
+    let mut rng = match seed {
+        Some(value) => ChaCha8Rng::seed_from_u64(value),
+        None => ChaCha8Rng::from_entropy(),
+    };
 
     let mut images = images;
     images.shuffle(&mut rng);
 
     images
-}
\ No newline at end of file
+}
+
+fn modified_time(path: &str) -> SystemTime {
+    match metadata(path).and_then(|value| value.modified()) {
+        Ok(value) => value,
+        Err(_) => SystemTime::UNIX_EPOCH,
+    }
+}
+
+fn natural_compare(a: &str, b: &str) -> Ordering {
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+
+    loop {
+        match (a.peek(), b.peek()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let mut x = String::new();
+                while let Some(digit) = a.next_if(|c| c.is_ascii_digit()) {
+                    x.push(digit);
+                }
+
+                let mut y = String::new();
+                while let Some(digit) = b.next_if(|c| c.is_ascii_digit()) {
+                    y.push(digit);
+                }
+
+                let x = x.trim_start_matches('0');
+                let y = y.trim_start_matches('0');
+
+                let ordering = x.len().cmp(&y.len()).then_with(|| x.cmp(y));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            },
+            (Some(x), Some(y)) => {
+                let ordering = x.cmp(y);
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+
+                a.next();
+                b.next();
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::OrientationFilter;
+
+    fn image_names(count: usize) -> Vec<String> {
+        (0..count).map(|index| format!("image{}.png", index)).collect()
+    }
+
+    #[test]
+    fn same_seed_gives_same_order() {
+        let seed = generate_seed();
+        assert_eq!(shuffle_images(image_names(50), Some(seed)), shuffle_images(image_names(50), Some(seed)));
+    }
+
+    #[test]
+    fn different_seeds_give_different_orders() {
+        assert_ne!(shuffle_images(image_names(50), Some(1)), shuffle_images(image_names(50), Some(2)));
+    }
+
+    #[test]
+    fn generated_seeds_fit_in_session_codes() {
+        for _ in 0..100 {
+            assert!(generate_seed() < 1_000_000);
+        }
+    }
+
+    #[test]
+    fn natural_compare_orders_numbers_by_value() {
+        assert_eq!(natural_compare("img2", "img10"), Ordering::Less);
+        assert_eq!(natural_compare("img10", "img2"), Ordering::Greater);
+        assert_eq!(natural_compare("img10", "img10"), Ordering::Equal);
+    }
+
+    #[test]
+    fn natural_compare_ignores_leading_zeros() {
+        assert_eq!(natural_compare("img002", "img10"), Ordering::Less);
+        assert_eq!(natural_compare("img01", "img1"), Ordering::Equal);
+    }
+
+    #[test]
+    fn natural_compare_handles_prefixes_and_text() {
+        assert_eq!(natural_compare("img", "img1"), Ordering::Less);
+        assert_eq!(natural_compare("a10b", "a10c"), Ordering::Less);
+        assert_eq!(natural_compare("b1", "a2"), Ordering::Greater);
+    }
+
+    #[test]
+    fn natural_order_sorts_file_names() {
+        let images = vec!["img10.png".to_owned(), "img2.png".to_owned(), "img1.png".to_owned()];
+        assert_eq!(order_images(images, SelectionMode::Natural, None), vec!["img1.png", "img2.png", "img10.png"]);
+    }
+
+    fn session_with_entries() -> Session {
+        let mut session = Session::new();
+        session.images = vec!["a".into(), "b".into(), "c".into()];
+        session.durations = vec![30, 60, 120];
+        session.time_spent = vec![30, 5];
+        session.extensions = vec![0, 15];
+        session
+    }
+
+    #[test]
+    fn dropping_an_image_keeps_per_image_state_aligned() {
+        let mut session = session_with_entries();
+        session.current_image = 1;
+        session.drop_current_image();
+
+        assert_eq!(session.images, vec!["a", "c"]);
+        assert_eq!(session.durations, vec![30, 60, 120]);
+        assert_eq!(session.time_spent, vec![30]);
+        assert_eq!(session.extensions, vec![0]);
+        assert_eq!(session.image_time(), 60);
+    }
+
+    #[test]
+    fn dropped_images_are_replaced_from_the_pool() {
+        let mut session = session_with_entries();
+        session.pool = vec!["d".into()];
+        session.current_image = 0;
+        session.drop_current_image();
+
+        assert_eq!(session.images, vec!["b", "c", "d"]);
+        assert_eq!(session.durations, vec![30, 60, 120]);
+        assert_eq!(session.image_time(), 30);
+        assert_eq!(session.time_spent, vec![5]);
+        assert_eq!(session.extensions, vec![15]);
+    }
+
+    #[test]
+    fn dropped_images_count_towards_progress() {
+        let mut session = session_with_entries();
+        session.offset = Some(10);
+        session.current_image = 1;
+        session.drop_current_image();
+
+        assert_eq!(session.progress(), Some(13));
+    }
+
+    #[test]
+    fn only_endless_sessions_preview_the_pool() {
+        let mut session = session_with_entries();
+        session.pool = vec!["d".into()];
+        session.current_image = 2;
+
+        assert_eq!(session.upcoming_image(), None);
+
+        session.endless = true;
+
+        assert_eq!(session.upcoming_image(), Some("d".into()));
+    }
+
+    #[test]
+    fn prompts_follow_images_across_drops() {
+        let mut session = session_with_entries();
+        session.deck = (1..=10)
+            .map(|index| PromptCard {
+                text: format!("Prompt {}", index),
+                weight: 1,
+            })
+            .collect();
+        session.current_image = 2;
+        let prompt = session.current_prompt();
+
+        session.current_image = 0;
+        session.drop_current_image();
+        session.current_image = 1;
+
+        assert_eq!(session.current_image().as_deref(), Some("c"));
+        assert_eq!(session.current_prompt(), prompt);
+    }
+
+    #[test]
+    fn drawings_stay_with_their_images_across_drops() {
+        let mut session = session_with_entries();
+        session.current_image = 2;
+        session.attach_drawing("c".into(), "drawing.png".into());
+
+        session.current_image = 0;
+        session.drop_current_image();
+        session.current_image = 1;
+
+        assert_eq!(session.current_drawing(), Some("drawing.png".into()));
+
+        session.current_image = 0;
+
+        assert_eq!(session.current_drawing(), None);
+    }
+
+    #[test]
+    fn dimension_filters_keep_remote_images() {
+        let mut settings = Settings::new();
+        settings.orientation = OrientationFilter::Portrait;
+        settings.min_width = 500;
+
+        let images = vec!["http://server/a.png".to_owned(), "https://server/b.png".to_owned()];
+
+        assert_eq!(filter_dimensions(images.clone(), &settings, "http://server/manifest.json"), images);
+    }
+
+    #[test]
+    fn unique_selection_skips_duplicates_and_advances_the_offset() {
+        let images: Vec<String> = ["a", "b", "c", "d", "e", "f"].iter().map(|image| image.to_string()).collect();
+        let hashes: HashMap<&str, u64> = [("a", 0), ("b", 1), ("c", u64::MAX), ("d", 3), ("e", u64::MAX << 32), ("f", 7)].into_iter().collect();
+
+        let selection = pick_unique_images(
+            images,
+            3,
+            Some(1),
+            |image| hashes.get(image).copied(),
+            |image| match image {
+                "d" => 4,
+                _ => 1,
+            },
+        );
+
+        assert_eq!(selection.images, vec!["d", "c", "e"]);
+        assert_eq!(selection.pool, vec!["f", "a"]);
+        assert_eq!(selection.offset, Some(2));
+        assert_eq!(selection.duplicates, vec![vec!["b".to_owned(), "d".to_owned()]]);
+        assert_eq!(selection.scanned, 4);
+    }
+}
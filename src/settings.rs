@@ -1,23 +1,316 @@
 use std::time::Duration;
 use std::env::var_os;
 
+use crate::index::Orientation;
+use crate::planner::{fit_durations, plan_session, segment_durations, PoseCurve};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SelectionMode {
+    Random,
+    Alphabetical,
+    Natural,
+    Modified,
+    Continue,
+}
+
+impl SelectionMode {
+    pub fn id(&self) -> &'static str {
+        match self {
+            SelectionMode::Random => "random",
+            SelectionMode::Alphabetical => "alphabetical",
+            SelectionMode::Natural => "natural",
+            SelectionMode::Modified => "modified",
+            SelectionMode::Continue => "continue",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "random" => Some(SelectionMode::Random),
+            "alphabetical" => Some(SelectionMode::Alphabetical),
+            "natural" => Some(SelectionMode::Natural),
+            "modified" => Some(SelectionMode::Modified),
+            "continue" => Some(SelectionMode::Continue),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SessionSource {
+    Folder,
+    List,
+    Server,
+    Favourites,
+}
+
+impl SessionSource {
+    pub fn id(&self) -> &'static str {
+        match self {
+            SessionSource::Folder => "folder",
+            SessionSource::List => "list",
+            SessionSource::Server => "server",
+            SessionSource::Favourites => "favourites",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "folder" => Some(SessionSource::Folder),
+            "list" => Some(SessionSource::List),
+            "server" => Some(SessionSource::Server),
+            "favourites" => Some(SessionSource::Favourites),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OrientationFilter {
+    Any,
+    Portrait,
+    Landscape,
+    Square,
+}
+
+impl OrientationFilter {
+    pub fn id(&self) -> &'static str {
+        match self {
+            OrientationFilter::Any => "any",
+            OrientationFilter::Portrait => "portrait",
+            OrientationFilter::Landscape => "landscape",
+            OrientationFilter::Square => "square",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "any" => Some(OrientationFilter::Any),
+            "portrait" => Some(OrientationFilter::Portrait),
+            "landscape" => Some(OrientationFilter::Landscape),
+            "square" => Some(OrientationFilter::Square),
+            _ => None,
+        }
+    }
+
+    pub fn matches(&self, orientation: Orientation) -> bool {
+        match self {
+            OrientationFilter::Any => true,
+            OrientationFilter::Portrait => orientation == Orientation::Portrait,
+            OrientationFilter::Landscape => orientation == Orientation::Landscape,
+            OrientationFilter::Square => orientation == Orientation::Square,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimerDisplay {
+    Countdown,
+    Elapsed,
+}
+
+impl TimerDisplay {
+    pub fn id(&self) -> &'static str {
+        match self {
+            TimerDisplay::Countdown => "countdown",
+            TimerDisplay::Elapsed => "elapsed",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "countdown" => Some(TimerDisplay::Countdown),
+            "elapsed" => Some(TimerDisplay::Elapsed),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Settings {
+    pub source: SessionSource,
     pub folder: String,
+    pub list_file: String,
+    pub server_url: String,
     pub images_number: usize,
     pub duration: Option<Duration>,
     pub pause: Option<Duration>,
+    pub seed: Option<u64>,
+    pub selection_mode: SelectionMode,
+    pub tag_query: String,
+    pub orientation: OrientationFilter,
+    pub min_width: u32,
+    pub min_height: u32,
+    pub min_aspect_ratio: Option<f64>,
+    pub max_aspect_ratio: Option<f64>,
+    pub skip_duplicates: bool,
+    pub watch_folder: bool,
+    pub endless: bool,
+    pub time_budget: Option<Duration>,
+    pub pose_curve: PoseCurve,
+    pub auto_advance: bool,
+    pub timer_display: TimerDisplay,
+    pub pause_on_focus_loss: bool,
+    pub pause_when_idle: bool,
+    pub memory_time: Option<Duration>,
+    pub reveal_time: Duration,
+    pub intermission_prompt: bool,
+    pub intermission_preview: bool,
+    pub intermission_skip: bool,
+    pub exercise_prompts: bool,
 }
 
 impl Settings {
     pub fn new() -> Self {
         Self {
+            source: SessionSource::Folder,
             folder: resolve_home_folder("~/Pictures"),
+            list_file: "".into(),
+            server_url: "".into(),
             images_number: 10,
             duration: None,
             pause: None,
+            seed: None,
+            selection_mode: SelectionMode::Random,
+            tag_query: "".into(),
+            orientation: OrientationFilter::Any,
+            min_width: 0,
+            min_height: 0,
+            min_aspect_ratio: None,
+            max_aspect_ratio: None,
+            skip_duplicates: false,
+            watch_folder: false,
+            endless: false,
+            time_budget: None,
+            pose_curve: PoseCurve::ShortToLong,
+            auto_advance: true,
+            timer_display: TimerDisplay::Countdown,
+            pause_on_focus_loss: false,
+            pause_when_idle: false,
+            memory_time: None,
+            reveal_time: Duration::from_secs(15),
+            intermission_prompt: true,
+            intermission_preview: false,
+            intermission_skip: true,
+            exercise_prompts: false,
+        }
+    }
+
+    pub fn filters_dimensions(&self) -> bool {
+        self.orientation != OrientationFilter::Any
+            || self.min_width > 0
+            || self.min_height > 0
+            || self.min_aspect_ratio.is_some()
+            || self.max_aspect_ratio.is_some()
+    }
+
+    pub fn matches_dimensions(&self, width: u32, height: u32) -> bool {
+        if width == 0 || height == 0 {
+            return false;
+        }
+
+        if !self.orientation.matches(Orientation::from_dimensions(width, height)) {
+            return false;
+        }
+
+        if width < self.min_width || height < self.min_height {
+            return false;
+        }
+
+        let aspect_ratio = width as f64 / height as f64;
+
+        if let Some(value) = self.min_aspect_ratio {
+            if aspect_ratio < value {
+                return false;
+            }
+        }
+
+        if let Some(value) = self.max_aspect_ratio {
+            if aspect_ratio > value {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    pub fn advances_automatically(&self) -> bool {
+        self.auto_advance && (self.duration.is_some() || (self.time_budget.is_some() && !self.endless))
+    }
+
+    pub fn memory_phase(&self) -> Duration {
+        if !self.advances_automatically() {
+            return Duration::ZERO;
+        }
+
+        match self.memory_time {
+            Some(value) if !value.is_zero() => value + self.reveal_time,
+            _ => Duration::ZERO,
         }
     }
+
+    pub fn planned_durations(&self) -> Option<Vec<Duration>> {
+        if self.endless {
+            return None;
+        }
+
+        let budget = self.time_budget?;
+        let memory = self.memory_phase();
+        let pause = self.pause.unwrap_or_default();
+
+        let segments = plan_session(budget.saturating_sub(memory), self.pose_curve, self.duration, Some(pause + memory));
+
+        Some(segment_durations(&segments))
+    }
+
+    pub fn fitted_durations(&self, images: usize) -> Option<Vec<Duration>> {
+        let durations = self.planned_durations()?;
+        let budget = self.time_budget?;
+        let memory = self.memory_phase();
+        let pause = self.pause.unwrap_or_default();
+
+        Some(fit_durations(&durations, images, budget.saturating_sub(memory), pause + memory))
+    }
+
+    pub fn session_images(&self) -> usize {
+        match self.planned_durations() {
+            Some(value) => value.len(),
+            None => self.images_number,
+        }
+    }
+
+    pub fn estimated_time(&self, images: usize) -> Option<Duration> {
+        let durations = match self.fitted_durations(images) {
+            Some(value) => value,
+            None => vec![self.duration?; images],
+        };
+
+        let durations = &durations[..usize::min(images, durations.len())];
+
+        if durations.is_empty() {
+            return Some(Duration::ZERO);
+        }
+
+        let pause = self.pause.unwrap_or_default();
+        let memory = self.memory_phase();
+        let images = durations.len() as u32;
+
+        Some(durations.iter().sum::<Duration>() + memory * images + pause * (images - 1))
+    }
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let hours = seconds / 3600;
+    let minutes = seconds % 3600 / 60;
+    let seconds = seconds % 60;
+
+    match (hours, minutes, seconds) {
+        (0, 0, seconds) => format!("{} s", seconds),
+        (0, minutes, 0) => format!("{} min", minutes),
+        (0, minutes, seconds) => format!("{} min {} s", minutes, seconds),
+        (hours, minutes, _) => format!("{} h {} min", hours, minutes),
+    }
 }
 
 fn resolve_home_folder<T: Into<String>>(path: T) -> String {
@@ -40,4 +333,84 @@ fn read_home_from_environment_variables() -> Option<String> {
     let value = value.to_str()?;
 
     Some(value.to_owned())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn any_orientation_matches_readable_images_only() {
+        let settings = Settings::new();
+
+        assert!(!settings.filters_dimensions());
+        assert!(settings.matches_dimensions(2, 3));
+        assert!(!settings.matches_dimensions(0, 0));
+    }
+
+    #[test]
+    fn orientation_filter_rejects_other_orientations() {
+        let mut settings = Settings::new();
+        settings.orientation = OrientationFilter::Portrait;
+
+        assert!(settings.filters_dimensions());
+        assert!(settings.matches_dimensions(2, 3));
+        assert!(!settings.matches_dimensions(3, 2));
+        assert!(!settings.matches_dimensions(2, 2));
+    }
+
+    #[test]
+    fn orientation_filter_never_matches_unknown_orientation() {
+        for filter in [OrientationFilter::Portrait, OrientationFilter::Landscape, OrientationFilter::Square] {
+            assert!(!filter.matches(Orientation::Unknown));
+        }
+
+        assert!(OrientationFilter::Any.matches(Orientation::Unknown));
+    }
+
+    #[test]
+    fn orientation_filter_ids_round_trip() {
+        for filter in [OrientationFilter::Any, OrientationFilter::Portrait, OrientationFilter::Landscape, OrientationFilter::Square] {
+            assert_eq!(OrientationFilter::from_id(filter.id()), Some(filter));
+        }
+
+        assert_eq!(OrientationFilter::from_id("unknown"), None);
+    }
+
+    #[test]
+    fn small_pools_stretch_the_plan_to_the_budget() {
+        let mut settings = Settings::new();
+        settings.time_budget = Some(Duration::from_secs(30 * 60));
+        settings.pause = Some(Duration::from_secs(10));
+
+        let planned = settings.session_images();
+        let estimate = settings.estimated_time(4).unwrap();
+
+        assert!(planned > 4);
+        assert_eq!(settings.fitted_durations(4).unwrap().len(), 4);
+        assert!(estimate <= Duration::from_secs(30 * 60));
+        assert!(estimate > Duration::from_secs(29 * 60));
+    }
+
+    #[test]
+    fn estimates_include_the_reveal_after_memory_drawing() {
+        let mut settings = Settings::new();
+        settings.duration = Some(Duration::from_secs(60));
+        settings.memory_time = Some(Duration::from_secs(30));
+
+        assert_eq!(settings.memory_phase(), Duration::from_secs(45));
+        assert_eq!(settings.estimated_time(2), Some(Duration::from_secs(210)));
+
+        settings.auto_advance = false;
+
+        assert_eq!(settings.memory_phase(), Duration::ZERO);
+        assert_eq!(settings.estimated_time(2), Some(Duration::from_secs(120)));
+
+        settings.auto_advance = true;
+
+        settings.memory_time = None;
+
+        assert_eq!(settings.memory_phase(), Duration::ZERO);
+        assert_eq!(settings.estimated_time(2), Some(Duration::from_secs(120)));
+    }
+}
@@ -0,0 +1,681 @@
+use std::fs::{create_dir_all, read, read_dir, read_to_string, write};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::archive::{image_exists, is_archive, read_images_in_archive};
+use crate::http::{http_get, resolve_url};
+use crate::image_list::{ImageList, FAVOURITES_FILE};
+use crate::settings::{SessionSource, Settings};
+use crate::index::ImageIndex;
+use crate::storage::{cache_path, hash_text};
+use crate::tags::is_sidecar;
+
+pub const DROPPED_LIST_FILE: &str = "dropped.m3u";
+
+pub struct PreparedImages {
+    pub images: Vec<String>,
+    pub failures: Vec<String>,
+}
+
+impl PreparedImages {
+    pub fn warning(&self) -> Option<String> {
+        match self.failures.as_slice() {
+            [] => None,
+            [failure] => Some(failure.clone()),
+            [failure, ..] => Some(format!("{} of {} images could not be downloaded. {}", self.failures.len(), self.images.len(), failure)),
+        }
+    }
+}
+
+pub trait ImageSource {
+    fn name(&self) -> String;
+    fn images(&self) -> Result<Vec<String>, String>;
+
+    fn preview_images(&self) -> Result<Vec<String>, String> {
+        self.images()
+    }
+
+    fn prepare(&self, images: Vec<String>, _progress: &dyn Fn(usize, usize)) -> Result<PreparedImages, String> {
+        Ok(PreparedImages {
+            images,
+            failures: vec![],
+        })
+    }
+
+    fn remote(&self) -> bool {
+        false
+    }
+
+    fn folder(&self) -> Option<String> {
+        None
+    }
+}
+
+pub fn create_source(settings: &Settings) -> Box<dyn ImageSource> {
+    match settings.source {
+        SessionSource::Folder if is_archive(&settings.folder) => Box::new(ArchiveSource::new(&settings.folder)),
+        SessionSource::Folder => Box::new(FolderSource::new(&settings.folder)),
+        SessionSource::List => Box::new(ListFileSource::new(&settings.list_file)),
+        SessionSource::Server => Box::new(ServerSource::new(&settings.server_url, cache_path("server"))),
+        SessionSource::Favourites => Box::new(FavouritesSource::new(ImageList::load(FAVOURITES_FILE))),
+    }
+}
+
+pub struct FolderSource {
+    folder: String,
+}
+
+impl FolderSource {
+    pub fn new(folder: &str) -> Self {
+        Self {
+            folder: folder.to_owned(),
+        }
+    }
+}
+
+impl ImageSource for FolderSource {
+    fn name(&self) -> String {
+        self.folder.clone()
+    }
+
+    fn folder(&self) -> Option<String> {
+        Some(self.folder.clone())
+    }
+
+    fn images(&self) -> Result<Vec<String>, String> {
+        let mut index = ImageIndex::load(&self.folder);
+        index.refresh(|_, _| {})?;
+        index.save();
+
+        Ok(index.images())
+    }
+
+    fn preview_images(&self) -> Result<Vec<String>, String> {
+        let mut index = ImageIndex::load(&self.folder);
+        index.refresh(|_, _| {})?;
+
+        Ok(index.images())
+    }
+}
+
+pub struct ArchiveSource {
+    archive: String,
+}
+
+impl ArchiveSource {
+    pub fn new(archive: &str) -> Self {
+        Self {
+            archive: archive.to_owned(),
+        }
+    }
+}
+
+impl ImageSource for ArchiveSource {
+    fn name(&self) -> String {
+        self.archive.clone()
+    }
+
+    fn images(&self) -> Result<Vec<String>, String> {
+        read_images_in_archive(&self.archive)
+    }
+}
+
+pub struct ListFileSource {
+    path: String,
+}
+
+impl ListFileSource {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_owned(),
+        }
+    }
+}
+
+impl ImageSource for ListFileSource {
+    fn name(&self) -> String {
+        self.path.clone()
+    }
+
+    fn images(&self) -> Result<Vec<String>, String> {
+        let content = match read_to_string(&self.path) {
+            Ok(value) => value,
+            Err(_) => return Err(format!("Failed to read image list {}", self.path)),
+        };
+
+        let folder = match Path::new(&self.path).parent() {
+            Some(value) => value.to_path_buf(),
+            None => Path::new(".").to_path_buf(),
+        };
+
+        Ok(parse_image_list(&content, &folder))
+    }
+}
+
+pub struct ServerSource {
+    url: String,
+    cache: PathBuf,
+}
+
+impl ServerSource {
+    pub fn new(url: &str, cache: PathBuf) -> Self {
+        Self {
+            url: url.trim().to_owned(),
+            cache,
+        }
+    }
+
+    fn cached_file(&self, url: &str) -> PathBuf {
+        let name = url.rsplit('/').next().unwrap_or("");
+        let extension = Path::new(name)
+            .extension()
+            .and_then(|value| value.to_str())
+            .unwrap_or("image");
+
+        self.cache.join(format!("{:016x}.{}", hash_text(url), extension))
+    }
+}
+
+impl ImageSource for ServerSource {
+    fn name(&self) -> String {
+        self.url.clone()
+    }
+
+    fn remote(&self) -> bool {
+        true
+    }
+
+    fn images(&self) -> Result<Vec<String>, String> {
+        let manifest_file = self.cache.join(format!("{:016x}.json", hash_text(&self.url)));
+
+        let manifest = match http_get(&self.url) {
+            Ok(value) => {
+                if create_dir_all(&self.cache).is_ok() {
+                    let _ = write(&manifest_file, &value);
+                }
+                value
+            },
+            Err(error) => match read(&manifest_file) {
+                Ok(value) => value,
+                Err(_) => return Err(error),
+            },
+        };
+
+        parse_manifest(&manifest, &self.url)
+    }
+
+    fn prepare(&self, images: Vec<String>, progress: &dyn Fn(usize, usize)) -> Result<PreparedImages, String> {
+        if create_dir_all(&self.cache).is_err() {
+            return Err("Failed to create image cache".into());
+        }
+
+        let mut prepared = PreparedImages {
+            images: vec![],
+            failures: vec![],
+        };
+
+        for (index, url) in images.iter().enumerate() {
+            progress(index, images.len());
+
+            let file = self.cached_file(url);
+
+            if !file.is_file() {
+                let downloaded = match http_get(url) {
+                    Ok(bytes) => match write(&file, bytes) {
+                        Ok(_) => Ok(()),
+                        Err(_) => Err(format!("Failed to save {} to the image cache", url)),
+                    },
+                    Err(error) => Err(error),
+                };
+
+                if let Err(error) = downloaded {
+                    prepared.failures.push(error);
+                    prepared.images.push(url.clone());
+                    continue;
+                }
+            }
+
+            match file.to_str() {
+                Some(file) => prepared.images.push(file.to_owned()),
+                None => prepared.images.push(url.clone()),
+            }
+        }
+
+        progress(images.len(), images.len());
+
+        if !images.is_empty() && prepared.failures.len() == images.len() {
+            return Err(format!("Failed to download images from {}. {}", self.url, prepared.failures[0]));
+        }
+
+        Ok(prepared)
+    }
+}
+
+pub struct FavouritesSource {
+    favourites: ImageList,
+}
+
+impl FavouritesSource {
+    pub fn new(favourites: ImageList) -> Self {
+        Self {
+            favourites,
+        }
+    }
+}
+
+impl ImageSource for FavouritesSource {
+    fn name(&self) -> String {
+        "favourites".into()
+    }
+
+    fn images(&self) -> Result<Vec<String>, String> {
+        let images = self.favourites
+            .images()
+            .iter()
+            .filter(|image| image_exists(image))
+            .cloned()
+            .collect();
+
+        Ok(images)
+    }
+}
+
+pub fn read_images_in_folder(folder: &str) -> Result<Vec<String>, String> {
+    let entries = match read_dir(folder) {
+        Ok(value) => value,
+        Err(_) => return Err(format!("Failed to read folder {}", folder)),
+    };
+
+    let mut images = vec![];
+
+    for entry in entries.flatten() {
+        if entry.path().is_dir() || is_sidecar(&entry.path()) {
+            continue;
+        }
+
+        if let Some(name) = entry.file_name().to_str() {
+            let name = format!("{}/{}", folder, name);
+
+            if is_archive(&name) {
+                if let Ok(archive_images) = read_images_in_archive(&name) {
+                    images.extend(archive_images);
+                }
+                continue;
+            }
+
+            images.push(name);
+        }
+    }
+    
+    Ok(images)
+}
+
+pub fn expand_dropped_paths(paths: &[String]) -> Vec<String> {
+    let mut images = vec![];
+
+    for path in paths {
+        if Path::new(path).is_dir() {
+            if let Ok(folder_images) = read_images_in_folder(path) {
+                images.extend(folder_images);
+            }
+            continue;
+        }
+
+        if is_archive(path) {
+            if let Ok(archive_images) = read_images_in_archive(path) {
+                images.extend(archive_images);
+            }
+            continue;
+        }
+
+        if !is_sidecar(Path::new(path)) {
+            images.push(path.clone());
+        }
+    }
+
+    images
+}
+
+pub fn parse_image_list(content: &str, folder: &Path) -> Vec<String> {
+    let mut images = vec![];
+
+    for line in content.lines() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let path = match line.strip_prefix("file://") {
+            Some(value) => match decode_file_uri(value) {
+                Some(value) => folder.join(value),
+                None => continue,
+            },
+            None => folder.join(line),
+        };
+
+        let path = match path.to_str() {
+            Some(value) => value.to_owned(),
+            None => continue,
+        };
+
+        if is_glob(&path) {
+            images.extend(expand_glob(&path));
+        }
+        else {
+            images.push(path);
+        }
+    }
+
+    images
+}
+
+fn decode_file_uri(path: &str) -> Option<String> {
+    let path = path.strip_prefix("localhost").unwrap_or(path);
+    let bytes = path.as_bytes();
+
+    let mut decoded = vec![];
+    let mut position = 0;
+
+    while position < bytes.len() {
+        if bytes[position] == b'%' {
+            let value = path.get(position + 1..position + 3)?;
+            decoded.push(u8::from_str_radix(value, 16).ok()?);
+            position += 3;
+        }
+        else {
+            decoded.push(bytes[position]);
+            position += 1;
+        }
+    }
+
+    String::from_utf8(decoded).ok()
+}
+
+pub fn parse_manifest(manifest: &[u8], url: &str) -> Result<Vec<String>, String> {
+    let manifest: Value = match serde_json::from_slice(manifest) {
+        Ok(value) => value,
+        Err(_) => return Err(format!("Invalid image manifest from {}", url)),
+    };
+
+    let entries = match &manifest {
+        Value::Array(values) => values,
+        Value::Object(values) => match values.get("images") {
+            Some(Value::Array(values)) => values,
+            _ => return Err(format!("Image manifest from {} has no images", url)),
+        },
+        _ => return Err(format!("Invalid image manifest from {}", url)),
+    };
+
+    let images = entries
+        .iter()
+        .filter_map(|entry| entry.as_str())
+        .map(|entry| resolve_url(url, entry))
+        .collect();
+
+    Ok(images)
+}
+
+pub fn save_image_list(path: &str, images: &[String]) -> io::Result<()> {
+    let mut content = String::from("#EXTM3U\n");
+
+    for image in images {
+        content.push_str(image);
+        content.push('\n');
+    }
+
+    write(path, content)
+}
+
+fn is_glob(path: &str) -> bool {
+    path.contains('*') || path.contains('?')
+}
+
+fn expand_glob(pattern: &str) -> Vec<String> {
+    let path = Path::new(pattern);
+
+    let (folder, name) = match (path.parent(), path.file_name().and_then(|value| value.to_str())) {
+        (Some(folder), Some(name)) => (folder, name),
+        _ => return vec![],
+    };
+
+    let folder = match folder.to_str() {
+        Some(value) => value,
+        None => return vec![],
+    };
+
+    let mut images: Vec<String> = match read_images_in_folder(folder) {
+        Ok(value) => value,
+        Err(_) => return vec![],
+    };
+
+    images.retain(|image| match Path::new(image).file_name().and_then(|value| value.to_str()) {
+        Some(file_name) => wildcard_match(name, file_name),
+        None => false,
+    });
+    images.sort();
+
+    images
+}
+
+fn wildcard_match(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let value: Vec<char> = value.chars().collect();
+
+    let mut p = 0;
+    let mut v = 0;
+    let mut star = None;
+    let mut star_value = 0;
+
+    while v < value.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == value[v]) {
+            p += 1;
+            v += 1;
+        }
+        else if p < pattern.len() && pattern[p] == '*' {
+            star = Some(p);
+            star_value = v;
+            p += 1;
+        }
+        else if let Some(position) = star {
+            p = position + 1;
+            star_value += 1;
+            v = star_value;
+        }
+        else {
+            return false;
+        }
+    }
+
+    while p < pattern.len() && pattern[p] == '*' {
+        p += 1;
+    }
+
+    p == pattern.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::tests::{serve, unreachable_url};
+    use std::env::temp_dir;
+    use std::fs::remove_dir_all;
+    use std::process;
+
+    fn test_folder(name: &str, files: &[&str]) -> PathBuf {
+        let folder = temp_dir().join(format!("quick-pose-source-{}-{}", name, process::id()));
+        let _ = remove_dir_all(&folder);
+        create_dir_all(&folder).unwrap();
+
+        for file in files {
+            write(folder.join(file), "").unwrap();
+        }
+
+        folder
+    }
+
+    #[test]
+    fn list_skips_comments_and_blank_lines() {
+        let content = "#EXTM3U\n\n#EXTINF:-1,Pose\n/images/a.png\n  \n# note\n/images/b.png\n";
+        assert_eq!(parse_image_list(content, Path::new("/lists")), vec!["/images/a.png", "/images/b.png"]);
+    }
+
+    #[test]
+    fn list_resolves_relative_paths_against_its_folder() {
+        let content = "a.png\nposes/b.png\n/absolute/c.png\n";
+        assert_eq!(parse_image_list(content, Path::new("/lists")), vec!["/lists/a.png", "/lists/poses/b.png", "/absolute/c.png"]);
+    }
+
+    #[test]
+    fn list_trims_whitespace_and_windows_line_endings() {
+        let content = "  a.png  \r\nb.png\r\n";
+        assert_eq!(parse_image_list(content, Path::new("/lists")), vec!["/lists/a.png", "/lists/b.png"]);
+    }
+
+    #[test]
+    fn list_decodes_file_uris() {
+        let content = "file:///images/a%20pose.png\nfile://localhost/images/b.png\nfile:///images/%C3%A9t%C3%A9.png\n";
+        assert_eq!(parse_image_list(content, Path::new("/lists")), vec!["/images/a pose.png", "/images/b.png", "/images/été.png"]);
+    }
+
+    #[test]
+    fn list_skips_malformed_file_uris() {
+        let content = "file:///images/a%2.png\nfile:///images/%FF.png\n/images/b.png\n";
+        assert_eq!(parse_image_list(content, Path::new("/lists")), vec!["/images/b.png"]);
+    }
+
+    #[test]
+    fn list_expands_wildcards() {
+        let folder = test_folder("glob", &["pose1.png", "pose2.png", "pose10.png", "other.png", "pose1.txt"]);
+        let images = parse_image_list("pose?.png\n", &folder);
+        let names: Vec<&str> = images.iter().filter_map(|image| Path::new(image).file_name()?.to_str()).collect();
+
+        assert_eq!(names, vec!["pose1.png", "pose2.png"]);
+
+        let images = parse_image_list("pose*\n", &folder);
+        assert_eq!(images.len(), 3);
+
+        remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn wildcard_matches_question_marks_and_stars() {
+        assert!(wildcard_match("*", ""));
+        assert!(wildcard_match("*", "anything.png"));
+        assert!(wildcard_match("a?c", "abc"));
+        assert!(!wildcard_match("a?c", "ac"));
+        assert!(wildcard_match("*.png", "pose.png"));
+        assert!(!wildcard_match("*.png", "pose.png.txt"));
+        assert!(wildcard_match("a*b*c", "aXXbYYc"));
+        assert!(wildcard_match("a*b*c", "abbbc"));
+        assert!(!wildcard_match("a*b*c", "acb"));
+        assert!(wildcard_match("**a", "a"));
+        assert!(!wildcard_match("", "a"));
+        assert!(wildcard_match("", ""));
+    }
+
+    #[test]
+    fn list_file_source_reads_lists() {
+        let folder = test_folder("list", &[]);
+        let list = folder.join("poses.m3u");
+        write(&list, "#EXTM3U\na.png\n").unwrap();
+
+        let source = ListFileSource::new(list.to_str().unwrap());
+        assert_eq!(source.images(), Ok(vec![folder.join("a.png").to_str().unwrap().to_owned()]));
+
+        let source = ListFileSource::new(folder.join("missing.m3u").to_str().unwrap());
+        assert!(source.images().is_err());
+
+        remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn server_source_downloads_into_the_cache() {
+        let server = serve(vec![
+            ("/manifest.json", br#"["a.png", "b.png"]"#.to_vec()),
+            ("/a.png", b"a".to_vec()),
+            ("/b.png", b"b".to_vec()),
+        ]);
+        let cache = test_folder("server-cache", &[]);
+        let source = ServerSource::new(&format!("{}/manifest.json", server), cache.clone());
+
+        let images = source.images().unwrap();
+        assert_eq!(images, vec![format!("{}/a.png", server), format!("{}/b.png", server)]);
+
+        let prepared = source.prepare(images, &|_, _| {}).unwrap();
+        assert_eq!(prepared.warning(), None);
+        assert_eq!(read(&prepared.images[0]).unwrap(), b"a");
+        assert_eq!(read(&prepared.images[1]).unwrap(), b"b");
+
+        remove_dir_all(cache).unwrap();
+    }
+
+    #[test]
+    fn server_source_keeps_failed_downloads_in_place() {
+        let server = serve(vec![
+            ("/manifest.json", br#"["a.png", "missing.png", "https://secure/c.png"]"#.to_vec()),
+            ("/a.png", b"a".to_vec()),
+        ]);
+        let cache = test_folder("server-failures", &[]);
+        let source = ServerSource::new(&format!("{}/manifest.json", server), cache.clone());
+
+        let images = source.images().unwrap();
+        let prepared = source.prepare(images.clone(), &|_, _| {}).unwrap();
+
+        assert_eq!(prepared.images.len(), 3);
+        assert_eq!(prepared.images[1], images[1]);
+        assert_eq!(prepared.images[2], "https://secure/c.png");
+        assert_eq!(prepared.failures.len(), 2);
+        assert!(prepared.warning().unwrap().starts_with("2 of 3 images"));
+
+        remove_dir_all(cache).unwrap();
+    }
+
+    #[test]
+    fn server_source_reports_progress() {
+        let server = serve(vec![("/a.png", b"a".to_vec())]);
+        let cache = test_folder("server-progress", &[]);
+        let source = ServerSource::new(&format!("{}/manifest.json", server), cache.clone());
+        let updates = std::cell::RefCell::new(vec![]);
+
+        source.prepare(vec![format!("{}/a.png", server)], &|done, total| updates.borrow_mut().push((done, total))).unwrap();
+        assert_eq!(updates.into_inner(), vec![(0, 1), (1, 1)]);
+
+        remove_dir_all(cache).unwrap();
+    }
+
+    #[test]
+    fn server_source_uses_the_cached_manifest_when_unreachable() {
+        let server = serve(vec![("/manifest.json", br#"["a.png"]"#.to_vec())]);
+        let cache = test_folder("server-offline", &[]);
+
+        let online = ServerSource::new(&format!("{}/manifest.json", server), cache.clone());
+        let images = online.images().unwrap();
+
+        let offline_url = format!("{}/manifest.json", unreachable_url());
+        let offline = ServerSource::new(&offline_url, cache.clone());
+        assert!(offline.images().is_err());
+
+        write(cache.join(format!("{:016x}.json", hash_text(&offline_url))), br#"["a.png"]"#).unwrap();
+        assert_eq!(offline.images().unwrap().len(), images.len());
+
+        let error = offline.prepare(offline.images().unwrap(), &|_, _| {}).err().unwrap();
+        assert!(error.starts_with("Failed to download images"), "{}", error);
+
+        remove_dir_all(cache).unwrap();
+    }
+
+    #[test]
+    fn favourites_source_skips_missing_images() {
+        let folder = test_folder("favourites", &["a.png"]);
+        let existing = folder.join("a.png").to_str().unwrap().to_owned();
+        let missing = folder.join("b.png").to_str().unwrap().to_owned();
+
+        let source = FavouritesSource::new(ImageList::with_images(FAVOURITES_FILE, vec![existing.clone(), missing]));
+        assert_eq!(source.images(), Ok(vec![existing]));
+
+        remove_dir_all(folder).unwrap();
+    }
+}
@@ -0,0 +1,65 @@
+use std::env::var_os;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::io;
+use std::path::PathBuf;
+
+pub fn data_path(name: &str) -> PathBuf {
+    xdg_folder("XDG_DATA_HOME", ".local/share").join(name)
+}
+
+pub fn cache_path(name: &str) -> PathBuf {
+    xdg_folder("XDG_CACHE_HOME", ".cache").join(name)
+}
+
+pub fn shared_cache_path(name: &str) -> PathBuf {
+    xdg_home("XDG_CACHE_HOME", ".cache").join(name)
+}
+
+fn xdg_folder(variable: &str, fallback: &str) -> PathBuf {
+    xdg_home(variable, fallback).join("quick-pose")
+}
+
+fn xdg_home(variable: &str, fallback: &str) -> PathBuf {
+    match var_os(variable) {
+        Some(value) if !value.is_empty() => PathBuf::from(value),
+        _ => match var_os("HOME") {
+            Some(value) => PathBuf::from(value).join(fallback),
+            None => PathBuf::from("."),
+        },
+    }
+}
+
+pub fn read_lines(name: &str) -> Vec<String> {
+    match read_to_string(data_path(name)) {
+        Ok(content) => content
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| line.to_owned())
+            .collect(),
+        Err(_) => vec![],
+    }
+}
+
+pub fn write_lines(name: &str, lines: &[String]) -> io::Result<()> {
+    let path = data_path(name);
+
+    if let Some(folder) = path.parent() {
+        create_dir_all(folder)?;
+    }
+
+    let mut content = lines.join("\n");
+    content.push('\n');
+
+    write(path, content)
+}
+
+pub fn hash_text(text: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+
+    for byte in text.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    hash
+}
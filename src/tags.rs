@@ -0,0 +1,324 @@
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::path::Path;
+
+use crate::storage::{read_lines, write_lines};
+
+const TAGS_FILE: &str = "tags";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TagQuery {
+    Tag(String),
+    Not(Box<TagQuery>),
+    And(Box<TagQuery>, Box<TagQuery>),
+    Or(Box<TagQuery>, Box<TagQuery>),
+}
+
+impl TagQuery {
+    pub fn parse(query: &str) -> Result<Option<Self>, String> {
+        let tokens = tokenize(query);
+
+        if tokens.is_empty() {
+            return Ok(None);
+        }
+
+        let mut position = 0;
+        let query = parse_or(&tokens, &mut position)?;
+
+        if position < tokens.len() {
+            return Err(format!("Unexpected \"{}\" in tag query", tokens[position]));
+        }
+
+        Ok(Some(query))
+    }
+
+    pub fn matches(&self, tags: &[String]) -> bool {
+        match self {
+            TagQuery::Tag(tag) => tags.contains(tag),
+            TagQuery::Not(query) => !query.matches(tags),
+            TagQuery::And(left, right) => left.matches(tags) && right.matches(tags),
+            TagQuery::Or(left, right) => left.matches(tags) || right.matches(tags),
+        }
+    }
+}
+
+fn tokenize(query: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut token = String::new();
+
+    for c in query.chars() {
+        if c.is_whitespace() || c == '(' || c == ')' {
+            if !token.is_empty() {
+                tokens.push(token.clone());
+                token.clear();
+            }
+
+            if !c.is_whitespace() {
+                tokens.push(c.to_string());
+            }
+        }
+        else {
+            token.push(c);
+        }
+    }
+
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+
+    tokens
+}
+
+fn is_keyword(token: &str, keyword: &str) -> bool {
+    token.eq_ignore_ascii_case(keyword)
+}
+
+fn parse_or(tokens: &[String], position: &mut usize) -> Result<TagQuery, String> {
+    let mut query = parse_and(tokens, position)?;
+
+    while *position < tokens.len() && is_keyword(&tokens[*position], "or") {
+        *position += 1;
+        let right = parse_and(tokens, position)?;
+        query = TagQuery::Or(Box::new(query), Box::new(right));
+    }
+
+    Ok(query)
+}
+
+fn parse_and(tokens: &[String], position: &mut usize) -> Result<TagQuery, String> {
+    let mut query = parse_not(tokens, position)?;
+
+    while *position < tokens.len() {
+        let token = &tokens[*position];
+
+        if is_keyword(token, "or") || token == ")" {
+            break;
+        }
+
+        if is_keyword(token, "and") {
+            *position += 1;
+        }
+
+        let right = parse_not(tokens, position)?;
+        query = TagQuery::And(Box::new(query), Box::new(right));
+    }
+
+    Ok(query)
+}
+
+fn parse_not(tokens: &[String], position: &mut usize) -> Result<TagQuery, String> {
+    let token = match tokens.get(*position) {
+        Some(value) => value,
+        None => return Err("Tag query ended unexpectedly".into()),
+    };
+
+    *position += 1;
+
+    if is_keyword(token, "not") {
+        let query = parse_not(tokens, position)?;
+        return Ok(TagQuery::Not(Box::new(query)));
+    }
+
+    if token == "(" {
+        let query = parse_or(tokens, position)?;
+
+        match tokens.get(*position) {
+            Some(value) if value == ")" => *position += 1,
+            _ => return Err("Missing \")\" in tag query".into()),
+        }
+
+        return Ok(query);
+    }
+
+    if token == ")" || is_keyword(token, "and") || is_keyword(token, "or") {
+        return Err(format!("Unexpected \"{}\" in tag query", token));
+    }
+
+    Ok(TagQuery::Tag(normalize_tag(token)))
+}
+
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+pub struct TagStore {
+    tags: HashMap<String, Vec<String>>,
+}
+
+impl TagStore {
+    pub fn load() -> Self {
+        let mut tags = HashMap::new();
+
+        for line in read_lines(TAGS_FILE) {
+            if let Some((path, values)) = line.split_once('\t') {
+                tags.insert(path.to_owned(), parse_tag_list(values));
+            }
+        }
+
+        Self {
+            tags,
+        }
+    }
+
+    pub fn save(&self) {
+        let mut lines: Vec<String> = self.tags
+            .iter()
+            .filter(|(_, tags)| !tags.is_empty())
+            .map(|(path, tags)| format!("{}\t{}", path, tags.join(",")))
+            .collect();
+
+        lines.sort();
+
+        if let Err(error) = write_lines(TAGS_FILE, &lines) {
+            eprintln!("Failed to save tags: {}", error);
+        }
+    }
+
+    pub fn image_tags(&self, image: &str) -> Vec<String> {
+        let mut tags = match self.tags.get(image) {
+            Some(value) => value.clone(),
+            None => vec![],
+        };
+
+        for tag in read_sidecar_tags(image) {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+
+        tags
+    }
+
+    pub fn add_tag(&mut self, image: &str, tag: &str) {
+        let tag = normalize_tag(tag);
+
+        if tag.is_empty() {
+            return;
+        }
+
+        let tags = self.tags.entry(image.to_owned()).or_default();
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+
+    pub fn toggle_tag(&mut self, image: &str, tag: &str) -> bool {
+        let tag = normalize_tag(tag);
+        let tags = self.tags.entry(image.to_owned()).or_default();
+
+        match tags.iter().position(|value| *value == tag) {
+            Some(position) => {
+                tags.remove(position);
+            },
+            None if !tag.is_empty() => tags.push(tag.clone()),
+            None => {},
+        }
+
+        self.image_tags(image).contains(&tag)
+    }
+}
+
+fn parse_tag_list(values: &str) -> Vec<String> {
+    values
+        .split([',', '\n'])
+        .map(normalize_tag)
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+fn read_sidecar_tags(image: &str) -> Vec<String> {
+    let sidecar = Path::new(image).with_extension("txt");
+
+    match read_to_string(sidecar) {
+        Ok(content) => parse_tag_list(&content),
+        Err(_) => vec![],
+    }
+}
+
+pub fn is_sidecar(path: &Path) -> bool {
+    match path.extension().and_then(|value| value.to_str()) {
+        Some(extension) => extension.eq_ignore_ascii_case("txt"),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(name: &str) -> Box<TagQuery> {
+        Box::new(TagQuery::Tag(name.into()))
+    }
+
+    fn tags(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn empty_query_parses_to_none() {
+        assert_eq!(TagQuery::parse("   "), Ok(None));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let query = TagQuery::parse("a or b and c").unwrap().unwrap();
+        assert_eq!(query, TagQuery::Or(tag("a"), Box::new(TagQuery::And(tag("b"), tag("c")))));
+    }
+
+    #[test]
+    fn adjacent_tags_are_joined_with_and() {
+        assert_eq!(TagQuery::parse("a b"), TagQuery::parse("a and b"));
+    }
+
+    #[test]
+    fn parentheses_override_precedence() {
+        let query = TagQuery::parse("(a or b) and c").unwrap().unwrap();
+        assert_eq!(query, TagQuery::And(Box::new(TagQuery::Or(tag("a"), tag("b"))), tag("c")));
+    }
+
+    #[test]
+    fn not_applies_to_the_next_term() {
+        let query = TagQuery::parse("not a and b").unwrap().unwrap();
+        assert_eq!(query, TagQuery::And(Box::new(TagQuery::Not(tag("a"))), tag("b")));
+
+        assert!(query.matches(&tags(&["b"])));
+        assert!(!query.matches(&tags(&["a", "b"])));
+        assert!(!query.matches(&tags(&[])));
+    }
+
+    #[test]
+    fn double_negation_matches_the_tag() {
+        let query = TagQuery::parse("not not a").unwrap().unwrap();
+        assert!(query.matches(&tags(&["a"])));
+        assert!(!query.matches(&tags(&["b"])));
+    }
+
+    #[test]
+    fn keywords_and_tags_are_case_insensitive() {
+        let query = TagQuery::parse("Figure OR NOT Hands").unwrap().unwrap();
+        assert_eq!(query, TagQuery::Or(tag("figure"), Box::new(TagQuery::Not(tag("hands")))));
+    }
+
+    #[test]
+    fn malformed_queries_are_rejected() {
+        assert!(TagQuery::parse("a or").is_err());
+        assert!(TagQuery::parse("(a or b").is_err());
+        assert!(TagQuery::parse("a )").is_err());
+        assert!(TagQuery::parse("and a").is_err());
+    }
+
+    #[test]
+    fn toggling_adds_and_removes_tags() {
+        let mut store = TagStore {
+            tags: HashMap::new(),
+        };
+
+        assert!(store.toggle_tag("/missing/a.png", " Hands "));
+        assert_eq!(store.image_tags("/missing/a.png"), tags(&["hands"]));
+
+        assert!(!store.toggle_tag("/missing/a.png", "hands"));
+        assert!(store.image_tags("/missing/a.png").is_empty());
+
+        assert!(!store.toggle_tag("/missing/a.png", "  "));
+    }
+}
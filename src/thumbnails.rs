@@ -0,0 +1,100 @@
+use gtk::gdk_pixbuf::{InterpType, Pixbuf};
+use std::fs::{canonicalize, create_dir_all, rename, set_permissions, Permissions};
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::archive::split_archive_path;
+use crate::image_loader::load_pixbuf;
+use crate::index::file_stats;
+use crate::storage::{cache_path, shared_cache_path};
+
+pub const THUMBNAIL_SIZE: i32 = 128;
+
+static SAVE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+pub fn thumbnail_uri(image: &str) -> String {
+    if split_archive_path(image).is_some() {
+        return image.to_owned();
+    }
+
+    let path = match canonicalize(image) {
+        Ok(value) => value.to_string_lossy().to_string(),
+        Err(_) => image.to_owned(),
+    };
+
+    let mut uri = String::from("file://");
+
+    for byte in path.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    uri
+}
+
+pub fn thumbnail_path(image: &str) -> PathBuf {
+    let name = format!("{:x}.png", md5::compute(thumbnail_uri(image)));
+
+    match split_archive_path(image) {
+        Some(_) => cache_path("thumbnails").join(name),
+        None => shared_cache_path("thumbnails/normal").join(name),
+    }
+}
+
+pub fn ensure_thumbnail(image: &str) -> Option<PathBuf> {
+    let path = thumbnail_path(image);
+    let (modified, _) = file_stats(image);
+    let modified = modified.to_string();
+
+    if let Ok(thumbnail) = Pixbuf::from_file(&path) {
+        if let Some(value) = thumbnail.option("tEXt::Thumb::MTime") {
+            if value.as_str() == modified {
+                return Some(path);
+            }
+        }
+    }
+
+    let thumbnail = match split_archive_path(image) {
+        Some(_) => scale_to_thumbnail(&load_pixbuf(image).ok()?)?,
+        None => match Pixbuf::file_info(image) {
+            Some((_, width, height)) if width <= THUMBNAIL_SIZE && height <= THUMBNAIL_SIZE => Pixbuf::from_file(image).ok()?,
+            _ => Pixbuf::from_file_at_scale(image, THUMBNAIL_SIZE, THUMBNAIL_SIZE, true).ok()?,
+        },
+    };
+
+    let folder = path.parent()?;
+    create_dir_all(folder).ok()?;
+    let _ = set_permissions(folder, Permissions::from_mode(0o700));
+
+    let uri = thumbnail_uri(image);
+    let temporary_path = path.with_extension(format!("{}-{}.tmp", process::id(), SAVE_COUNTER.fetch_add(1, Ordering::SeqCst)));
+
+    thumbnail
+        .savev(&temporary_path, "png", &[("tEXt::Thumb::URI", &uri), ("tEXt::Thumb::MTime", &modified)])
+        .ok()?;
+
+    let _ = set_permissions(&temporary_path, Permissions::from_mode(0o600));
+    rename(&temporary_path, &path).ok()?;
+
+    Some(path)
+}
+
+fn scale_to_thumbnail(pixbuf: &Pixbuf) -> Option<Pixbuf> {
+    let width = pixbuf.width();
+    let height = pixbuf.height();
+
+    if width <= THUMBNAIL_SIZE && height <= THUMBNAIL_SIZE {
+        return Some(pixbuf.clone());
+    }
+
+    let (width, height) = match width > height {
+        true => (THUMBNAIL_SIZE, height * THUMBNAIL_SIZE / width),
+        false => (width * THUMBNAIL_SIZE / height, THUMBNAIL_SIZE),
+    };
+
+    pixbuf.scale_simple(width.max(1), height.max(1), InterpType::Bilinear)
+}